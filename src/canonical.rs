@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+use url::Url;
+use crate::models::{Friends, Posts};
+
+/// 需要去除的跟踪参数前缀
+const TRACKING_PARAM_PREFIXES: [&str; 2] = ["utm_", "_hs"];
+
+/// 需要去除的跟踪参数
+const TRACKING_PARAMS: [&str; 7] = ["fbclid", "gclid", "mc_cid", "mc_eid", "spm", "ref_src", "yclid"];
+
+/// 清理URL：主机名小写、去掉默认端口、跟踪参数与锚点，无法解析时原样返回
+pub fn clean_url(link: &str) -> String {
+    let mut url = match Url::parse(link.trim()) {
+        Ok(url) => url,
+        Err(_) => return link.trim().to_string(),
+    };
+    
+    // Url 解析时已将 scheme 与主机名转为小写，并省略与 scheme 匹配的默认端口
+    url.set_fragment(None);
+    
+    let query: Vec<(String, String)> = url.query_pairs()
+        .filter(|(key, _)| !is_tracking_param(key))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    if query.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(query);
+    }
+    
+    url.to_string()
+}
+
+/// 生成用于比较的规范化键：在 clean_url 基础上忽略 http/https、www. 前缀和末尾斜杠
+pub fn canonical_key(link: &str) -> String {
+    let cleaned = clean_url(link);
+    let url = match Url::parse(&cleaned) {
+        Ok(url) => url,
+        Err(_) => return cleaned.trim_end_matches('/').to_lowercase(),
+    };
+    
+    let host = url.host_str().unwrap_or("");
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    let mut key = String::from(host);
+    if let Some(port) = url.port() {
+        key.push_str(&format!(":{}", port));
+    }
+    key.push_str(url.path().trim_end_matches('/'));
+    if let Some(query) = url.query() {
+        key.push('?');
+        key.push_str(query);
+    }
+    key
+}
+
+/// 生成稳定的文章标识：订阅源提供全局唯一的GUID时直接使用，否则使用规范化链接的哈希
+pub fn stable_id(guid: &str, link: &str) -> String {
+    if is_global_guid(guid) {
+        return guid.trim().to_string();
+    }
+    format!("{:016x}", fnv1a_64(canonical_key(link).as_bytes()))
+}
+
+/// GUID 形如URL、URN或 tag: URI 时才视为全局唯一，纯数字等站内编号可能在不同站点间重复
+fn is_global_guid(guid: &str) -> bool {
+    let guid = guid.trim();
+    !guid.is_empty() && (guid.contains(':') || guid.contains('/'))
+}
+
+/// FNV-1a 64位哈希，结果不随程序版本和运行环境变化
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// 判断是否为跟踪参数
+fn is_tracking_param(key: &str) -> bool {
+    let key = key.to_lowercase();
+    TRACKING_PARAMS.contains(&key.as_str())
+        || TRACKING_PARAM_PREFIXES.iter().any(|prefix| key.starts_with(prefix))
+}
+
+/// 按规范化链接去重友链，保留先出现的一项
+pub fn dedupe_friends(friends: Vec<Friends>) -> Vec<Friends> {
+    let mut seen = HashSet::new();
+    friends
+        .into_iter()
+        .filter(|friend| seen.insert(canonical_key(&friend.link)))
+        .collect()
+}
+
+/// 按规范化链接或全局唯一的GUID去重文章，重复时保留信息最完整的一条，并保持首次出现的位置
+pub fn dedupe_posts(posts: Vec<Posts>) -> Vec<Posts> {
+    let mut index_by_key: HashMap<String, usize> = HashMap::new();
+    let mut result: Vec<Posts> = Vec::new();
+    
+    for post in posts {
+        let mut keys = vec![format!("link:{}", canonical_key(&post.meta.link))];
+        if is_global_guid(&post.meta.guid) {
+            keys.push(format!("guid:{}", post.meta.guid.trim()));
+        }
+        
+        match keys.iter().find_map(|key| index_by_key.get(key).copied()) {
+            Some(idx) => {
+                if richness(&post) > richness(&result[idx]) {
+                    result[idx] = post;
+                }
+                for key in keys {
+                    index_by_key.entry(key).or_insert(idx);
+                }
+            }
+            None => {
+                for key in keys {
+                    index_by_key.insert(key, result.len());
+                }
+                result.push(post);
+            }
+        }
+    }
+    
+    result
+}
+
+/// 文章信息完整程度，用于在重复文章中择优
+fn richness(post: &Posts) -> usize {
+    let mut score = post.meta.content.len() + post.meta.summary.len();
+    if !post.meta.created.is_empty() {
+        score += 1;
+    }
+    if !post.avatar.is_empty() {
+        score += 1;
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PostMeta;
+    
+    fn post(link: &str, guid: &str, content: &str) -> Posts {
+        Posts {
+            meta: PostMeta {
+                title: String::from("标题"),
+                link: link.to_string(),
+                created: String::new(),
+                updated: String::new(),
+                content: content.to_string(),
+                summary: String::new(),
+                guid: guid.to_string(),
+                tags: Vec::new(),
+                cover: String::new(),
+                enclosures: Vec::new(),
+                lang: String::new(),
+                word_count: 0,
+                reading_minutes: 0,
+            },
+            author: String::from("作者"),
+            avatar: String::new(),
+            group: String::new(),
+            created_at: String::new(),
+        }
+    }
+    
+    #[test]
+    fn clean_url_removes_tracking_and_fragment() {
+        assert_eq!(
+            clean_url("HTTPS://Example.COM:443/post/1?utm_source=rss&id=2&fbclid=x#comments"),
+            "https://example.com/post/1?id=2"
+        );
+        assert_eq!(clean_url("https://example.com/?utm_medium=feed"), "https://example.com/");
+        assert_eq!(clean_url(" not a url "), "not a url");
+    }
+    
+    #[test]
+    fn canonical_key_ignores_scheme_www_and_trailing_slash() {
+        let key = canonical_key("https://example.com/blog");
+        assert_eq!(key, "example.com/blog");
+        assert_eq!(canonical_key("http://www.example.com/blog/"), key);
+        assert_eq!(canonical_key("https://www.example.com/blog/?utm_source=rss&utm_campaign=x"), key);
+        assert_ne!(canonical_key("https://example.com/blog?page=2"), key);
+        assert_ne!(canonical_key("https://example.com:8080/blog"), key);
+    }
+    
+    #[test]
+    fn dedupe_posts_keeps_richest_in_first_position() {
+        let posts = vec![
+            post("https://example.com/a/", "", ""),
+            post("https://example.com/b", "", ""),
+            post("http://www.example.com/a?utm_source=rss", "", "正文"),
+        ];
+        let result = dedupe_posts(posts);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].meta.content, "正文");
+        assert_eq!(result[1].meta.link, "https://example.com/b");
+    }
+    
+    #[test]
+    fn dedupe_posts_by_global_guid() {
+        let posts = vec![
+            post("https://example.com/a", "tag:example.com,2024:1", ""),
+            post("https://mirror.example.org/a", "tag:example.com,2024:1", ""),
+            // 站内编号不能跨站点去重
+            post("https://example.com/c", "42", ""),
+            post("https://other.example.net/c", "42", ""),
+        ];
+        assert_eq!(dedupe_posts(posts).len(), 3);
+    }
+}
@@ -1,6 +1,9 @@
+use std::collections::HashMap;
 use std::fs::File;
 use serde::{Serialize, Deserialize};
 use serde_yaml;
+use crate::error::{Error, Result};
+use crate::friend_list::FriendListFormat;
 
 /// CSS选择器规则
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,30 +12,310 @@ pub struct CssRules {
     pub link_page_rules: serde_yaml::Value,
 }
 
+/// 友链页配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkPageConfig {
+    pub link: String,
+    pub group: String,
+}
+
+/// 友链分组配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupConfig {
+    pub max_posts: Option<usize>,
+    pub exclude: bool,
+}
+
+/// 结构化友链列表来源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FriendSource {
+    pub path: String,
+    pub format: FriendListFormat,
+}
+
+/// 配置项友链及其单独设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FriendEntry {
+    pub name: String,
+    pub link: String,
+    pub avatar: String,
+    pub feed: String,
+    pub theme: String,
+    pub max_posts: Option<usize>,
+    pub timeout: Option<u64>,
+    pub headers: HashMap<String, String>,
+    pub user_agent: String,
+    pub enabled: bool,
+    pub group: String,
+    pub note: String,
+}
+
+impl FriendEntry {
+    /// 从 ["name", "link", "avatar", "suffix", "group"] 形式的行解析
+    fn from_row(row: &[String]) -> Option<FriendEntry> {
+        if row.len() < 3 {
+            return None;
+        }
+        Some(FriendEntry {
+            name: row[0].clone(),
+            link: row[1].clone(),
+            avatar: row[2].clone(),
+            feed: row.get(3).cloned().unwrap_or_default(),
+            theme: String::new(),
+            max_posts: None,
+            timeout: None,
+            headers: HashMap::new(),
+            user_agent: String::new(),
+            enabled: true,
+            group: row.get(4).cloned().unwrap_or_default(),
+            note: String::new(),
+        })
+    }
+    
+    /// 从 {name, link, avatar, feed, ...} 形式的映射解析
+    fn from_mapping(item: &serde_yaml::Value) -> Option<FriendEntry> {
+        let text = |key: &str| item[key].as_str().unwrap_or("").to_string();
+        let headers = item["headers"].as_mapping()
+            .map(|mapping| {
+                mapping.iter()
+                    .filter_map(|(k, v)| Some((k.as_str()?.to_string(), v.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        
+        Some(FriendEntry {
+            name: item["name"].as_str()?.to_string(),
+            link: item["link"].as_str()?.to_string(),
+            avatar: text("avatar"),
+            feed: text("feed"),
+            theme: text("theme"),
+            max_posts: item["max_posts"].as_u64().map(|n| n as usize),
+            timeout: item["timeout"].as_u64(),
+            headers,
+            user_agent: text("user_agent"),
+            enabled: item["enabled"].as_bool().unwrap_or(true),
+            group: text("group"),
+            note: text("note"),
+        })
+    }
+    
+    /// 自定义订阅地址，feed 为后缀时相对于主页地址解析
+    pub fn feed_url(&self) -> String {
+        if self.feed.is_empty() {
+            return String::new();
+        }
+        match url::Url::parse(&self.link).and_then(|base| base.join(&self.feed)) {
+            Ok(url) => url.to_string(),
+            Err(_) => self.feed.clone(),
+        }
+    }
+    
+    /// 是否需要单独的HTTP客户端，请求头随每个请求发送，不影响客户端
+    pub fn needs_own_client(&self) -> bool {
+        self.timeout.is_some() || !self.user_agent.is_empty()
+    }
+}
+
 /// 友链配置项
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FriendsLinksConfig {
     pub enable: bool,
-    pub json_api_or_path: String,
-    pub list: Vec<Vec<String>>,
+    pub sources: Vec<FriendSource>,
+    pub list: Vec<FriendEntry>,
+}
+
+/// 输出配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputConfig {
+    pub dir: String,
+    pub paginate: bool,
+    pub page_size: usize,
+    pub minified: bool,
+    pub per_author: bool,
+}
+
+/// 持久化状态配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateConfig {
+    pub path: String,
+    pub history_size: usize,
+}
+
+/// 友链健康判定配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthConfig {
+    pub dormant_days: u64,
+    pub dead_failures: usize,
+    pub dead_days: u64,
+    pub skip_dead: bool,
+    pub recheck_days: u64,
+}
+
+/// 友链互链检查配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReciprocalConfig {
+    pub enable: bool,
+    pub sites: Vec<String>,
+    pub paths: Vec<String>,
+    pub link_pages: HashMap<String, String>,
+    pub concurrency: usize,
+}
+
+/// 友链发现配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveryConfig {
+    pub enable: bool,
+    pub depth: usize,
+    pub max_sites: usize,
+    pub min_links: usize,
+    pub concurrency: usize,
+}
+
+/// 全文抓取配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FullContentConfig {
+    pub enable: bool,
+    pub concurrency: usize,
+}
+
+/// 正文处理配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentConfig {
+    pub sanitize: bool,
+    pub summary_length: usize,
+    pub include_content: bool,
+}
+
+/// HTTP请求配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpConfig {
+    pub max_body_size: usize,
+    pub check_content_type: bool,
+}
+
+/// 代理配置项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    pub url: String,
+    pub hosts: Vec<String>,
+    pub bypass: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// 判断请求的主机是否走代理：bypass 优先，hosts 非空时只代理其中的主机
+    /// 规则匹配主机本身及其子域名，可以写作 example.com、.example.com 或 *.example.com
+    pub fn should_proxy(&self, host: &str) -> bool {
+        if self.url.is_empty() {
+            return false;
+        }
+        let host = host.trim_end_matches('.').to_lowercase();
+        let matches = |rule: &String| {
+            let rule = rule.trim().trim_start_matches("*.").trim_start_matches('.').to_lowercase();
+            !rule.is_empty() && (host == rule || host.ends_with(&format!(".{}", rule)))
+        };
+        
+        if self.bypass.iter().any(matches) {
+            return false;
+        }
+        self.hosts.is_empty() || self.hosts.iter().any(matches)
+    }
+}
+
+/// 重定向检查配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedirectConfig {
+    pub enable: bool,
+}
+
+/// 文章过滤配置项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilterConfig {
+    pub include_title: Vec<String>,
+    pub exclude_title: Vec<String>,
+    pub include_categories: Vec<String>,
+    pub exclude_categories: Vec<String>,
+    pub min_content_length: usize,
+    pub friends: HashMap<String, FriendFilterConfig>,
+}
+
+/// 单个友链的额外排除规则
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FriendFilterConfig {
+    pub exclude_title: Vec<String>,
+    pub exclude_categories: Vec<String>,
+}
+
+/// 关键词标签配置项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaggingConfig {
+    pub enable: bool,
+    pub index: bool,
+    pub max_tags: usize,
+    pub keywords: Vec<TagKeywords>,
+}
+
+/// 一个标签及其关键词
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagKeywords {
+    pub tag: String,
+    pub keywords: Vec<String>,
 }
 
 /// FC配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FcSettings {
     pub enable_link_page: bool,
-    pub link_pages: Vec<String>,
+    pub link_pages: Vec<LinkPageConfig>,
     pub settings_friends_links: FriendsLinksConfig,
     pub block_sites: Vec<String>,
     pub max_posts_num: usize,
     pub outdate_clean: usize,
+    pub output: OutputConfig,
+    pub state: StateConfig,
+    pub health: HealthConfig,
+    pub reciprocal: ReciprocalConfig,
+    pub discovery: DiscoveryConfig,
+    pub groups: HashMap<String, GroupConfig>,
+    pub full_content: FullContentConfig,
+    pub content: ContentConfig,
+    pub filters: FilterConfig,
+    pub tagging: TaggingConfig,
+    pub http: HttpConfig,
+    pub redirects: RedirectConfig,
+    pub proxy: ProxyConfig,
     // 移除simple_mode字段，固化为true
 }
 
+impl FcSettings {
+    /// 分组单独设置的每个主页文章数上限
+    pub fn group_max_posts(&self, group: &str) -> Option<usize> {
+        self.groups.get(group).and_then(|g| g.max_posts)
+    }
+    
+    /// 分组是否从聚合文章中排除
+    pub fn is_group_excluded(&self, group: &str) -> bool {
+        self.groups.get(group).is_some_and(|g| g.exclude)
+    }
+}
+
+impl CssRules {
+    /// 只保留指定主题的文章页规则，用于强制友链使用某个主题
+    pub fn with_theme(&self, theme: &str) -> CssRules {
+        let mut post_page_rules = serde_yaml::Mapping::new();
+        if let Some(rules) = self.post_page_rules.get(theme) {
+            post_page_rules.insert(serde_yaml::Value::String(theme.to_string()), rules.clone());
+        }
+        CssRules {
+            post_page_rules: serde_yaml::Value::Mapping(post_page_rules),
+            link_page_rules: self.link_page_rules.clone(),
+        }
+    }
+}
+
 /// 从YAML文件读取CSS规则
-pub fn get_css_rules(path: &str) -> Result<CssRules, Box<dyn std::error::Error>> {
-    let file = File::open(path)?;
-    let rules: serde_yaml::Value = serde_yaml::from_reader(file)?;
+pub fn get_css_rules(path: &str) -> Result<CssRules> {
+    let file = File::open(path).map_err(|e| Error::config(path, e))?;
+    let rules: serde_yaml::Value = serde_yaml::from_reader(file).map_err(|e| Error::config(path, e))?;
     
     let post_page_rules = rules["post_page_rules"].clone();
     let link_page_rules = rules["link_page_rules"].clone();
@@ -44,9 +327,9 @@ pub fn get_css_rules(path: &str) -> Result<CssRules, Box<dyn std::error::Error>>
 }
 
 /// 从YAML文件读取FC配置
-pub fn get_fc_settings(path: &str) -> Result<FcSettings, Box<dyn std::error::Error>> {
-    let file = File::open(path)?;
-    let yaml: serde_yaml::Value = serde_yaml::from_reader(file)?;
+pub fn get_fc_settings(path: &str) -> Result<FcSettings> {
+    let file = File::open(path).map_err(|e| Error::config(path, e))?;
+    let yaml: serde_yaml::Value = serde_yaml::from_reader(file).map_err(|e| Error::config(path, e))?;
     
     // 友链页配置 - 新格式
     let enable_link_page = true; // 默认启用
@@ -56,7 +339,10 @@ pub fn get_fc_settings(path: &str) -> Result<FcSettings, Box<dyn std::error::Err
     if let Some(link_array) = yaml["LINK"].as_sequence() {
         for link_item in link_array {
             if let Some(link) = link_item["link"].as_str() {
-                link_pages.push(link.to_string());
+                link_pages.push(LinkPageConfig {
+                    link: link.to_string(),
+                    group: link_item["group"].as_str().unwrap_or("").to_string(),
+                });
             }
         }
     }
@@ -64,17 +350,42 @@ pub fn get_fc_settings(path: &str) -> Result<FcSettings, Box<dyn std::error::Err
     // 友链列表配置
     let friends_links = &yaml["SETTINGS_FRIENDS_LINKS"];
     let enable = friends_links["enable"].as_bool().unwrap_or(false);
-    let json_api_or_path = friends_links["json_api_or_path"].as_str().unwrap_or("")
-        .to_string();
     
+    // json_api_or_path 可以是单个地址，也可以是地址或 {path, format} 的列表
+    let source_items = match &friends_links["json_api_or_path"] {
+        serde_yaml::Value::Sequence(items) => items.clone(),
+        item => vec![item.clone()],
+    };
+    let sources = source_items
+        .iter()
+        .filter_map(|item| {
+            let (path, format) = match item {
+                serde_yaml::Value::String(path) => (path.as_str(), "auto"),
+                _ => (item["path"].as_str()?, item["format"].as_str().unwrap_or("auto")),
+            };
+            if path.is_empty() {
+                return None;
+            }
+            Some(FriendSource {
+                path: path.to_string(),
+                format: FriendListFormat::from_name(format),
+            })
+        })
+        .collect();
+    
+    // 列表项可以是 ["name", "link", "avatar", ...] 行，也可以是带单独设置的映射
     let list = friends_links["list"].as_sequence().unwrap_or(&vec![])
         .iter()
-        .map(|item| {
-            item.as_sequence().unwrap_or(&vec![])
-                .iter()
-                .filter_map(|v| v.as_str())
-                .map(|s| s.to_string())
-                .collect()
+        .filter_map(|item| match item {
+            serde_yaml::Value::Mapping(_) => FriendEntry::from_mapping(item),
+            _ => {
+                let row: Vec<String> = item.as_sequence().unwrap_or(&vec![])
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .map(|s| s.to_string())
+                    .collect();
+                FriendEntry::from_row(&row)
+            }
         })
         .collect();
     
@@ -89,17 +400,282 @@ pub fn get_fc_settings(path: &str) -> Result<FcSettings, Box<dyn std::error::Err
     let max_posts_num = yaml["MAX_POSTS_NUM"].as_i64().unwrap_or(0) as usize;
     let outdate_clean = yaml["OUTDATE_CLEAN"].as_i64().unwrap_or(0) as usize;
     
+    // 分组配置
+    let groups = yaml["GROUPS"].as_mapping()
+        .map(|mapping| {
+            mapping.iter()
+                .filter_map(|(name, group)| {
+                    Some((name.as_str()?.to_string(), GroupConfig {
+                        max_posts: group["max_posts"].as_u64().map(|n| n as usize),
+                        exclude: group["exclude"].as_bool().unwrap_or(false),
+                    }))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    
+    // 全文抓取配置
+    let full_content = &yaml["FULL_CONTENT"];
+    let full_content = FullContentConfig {
+        enable: full_content["enable"].as_bool().unwrap_or(false),
+        concurrency: full_content["concurrency"].as_u64().filter(|n| *n > 0).unwrap_or(4) as usize,
+    };
+    
+    // 正文处理配置
+    let content = &yaml["CONTENT"];
+    let content = ContentConfig {
+        sanitize: content["sanitize"].as_bool().unwrap_or(true),
+        summary_length: content["summary_length"].as_u64().unwrap_or(200) as usize,
+        include_content: content["include_content"].as_bool().unwrap_or(true),
+    };
+    
+    // 文章过滤配置，friends 的键为友链主页地址
+    let filters = &yaml["FILTERS"];
+    let filters = FilterConfig {
+        include_title: string_list(&filters["include_title"]),
+        exclude_title: string_list(&filters["exclude_title"]),
+        include_categories: string_list(&filters["include_categories"]),
+        exclude_categories: string_list(&filters["exclude_categories"]),
+        min_content_length: filters["min_content_length"].as_u64().unwrap_or(0) as usize,
+        friends: filters["friends"].as_mapping()
+            .map(|mapping| {
+                mapping.iter()
+                    .filter_map(|(link, rules)| {
+                        Some((link.as_str()?.to_string(), FriendFilterConfig {
+                            exclude_title: string_list(&rules["exclude_title"]),
+                            exclude_categories: string_list(&rules["exclude_categories"]),
+                        }))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    
+    // 关键词标签配置，keywords 的键为标签，值为关键词列表，未填写关键词时使用标签本身
+    let tagging = &yaml["TAGGING"];
+    let tagging = TaggingConfig {
+        enable: tagging["enable"].as_bool().unwrap_or(false),
+        index: tagging["index"].as_bool().unwrap_or(false),
+        max_tags: tagging["max_tags"].as_u64().unwrap_or(5) as usize,
+        keywords: tagging["keywords"].as_mapping()
+            .map(|mapping| {
+                mapping.iter()
+                    .filter_map(|(tag, keywords)| {
+                        let tag = tag.as_str()?.trim().to_string();
+                        let mut keywords = string_list(keywords);
+                        if keywords.is_empty() {
+                            keywords.push(tag.clone());
+                        }
+                        Some(TagKeywords { tag, keywords })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    
+    // HTTP请求配置
+    let http = &yaml["HTTP"];
+    let http = HttpConfig {
+        max_body_size: http["max_body_size"].as_u64().unwrap_or(10 * 1024 * 1024) as usize,
+        check_content_type: http["check_content_type"].as_bool().unwrap_or(true),
+    };
+    
+    // 代理配置
+    let proxy = &yaml["PROXY"];
+    let proxy = ProxyConfig {
+        url: proxy_url(proxy["url"].as_str().unwrap_or("")),
+        hosts: string_list(&proxy["hosts"]),
+        bypass: string_list(&proxy["bypass"]),
+    };
+    if !proxy.url.is_empty() {
+        let parsed = url::Url::parse(&proxy.url).map_err(|e| Error::config(path, format!("invalid PROXY url: {}", e)))?;
+        if !matches!(parsed.scheme(), "http" | "https" | "socks5" | "socks5h") {
+            return Err(Error::config(path, format!("unsupported PROXY scheme {}", parsed.scheme())));
+        }
+    }
+    
+    // 重定向检查配置
+    let redirects = RedirectConfig {
+        enable: yaml["REDIRECTS"]["enable"].as_bool().unwrap_or(true),
+    };
+    
+    // 输出配置
+    let output = &yaml["OUTPUT"];
+    let output = OutputConfig {
+        dir: output["dir"].as_str().unwrap_or(".").to_string(),
+        paginate: output["paginate"].as_bool().unwrap_or(false),
+        page_size: output["page_size"].as_i64().filter(|n| *n > 0).unwrap_or(50) as usize,
+        minified: output["minified"].as_bool().unwrap_or(false),
+        per_author: output["per_author"].as_bool().unwrap_or(false),
+    };
+    
+    // 持久化状态配置
+    let state = &yaml["STATE"];
+    let state = StateConfig {
+        path: state["path"].as_str().unwrap_or("data/state.json").to_string(),
+        history_size: state["history_size"].as_i64().filter(|n| *n > 0).unwrap_or(30) as usize,
+    };
+    
+    // 友链健康判定配置
+    let health = &yaml["HEALTH"];
+    let health = HealthConfig {
+        dormant_days: health["dormant_days"].as_u64().unwrap_or(90),
+        dead_failures: health["dead_failures"].as_u64().unwrap_or(10) as usize,
+        dead_days: health["dead_days"].as_u64().unwrap_or(30),
+        skip_dead: health["skip_dead"].as_bool().unwrap_or(false),
+        recheck_days: health["recheck_days"].as_u64().unwrap_or(7),
+    };
+    
+    // 互链检查配置，未填写本站地址时使用友链页所在站点
+    let reciprocal = &yaml["RECIPROCAL"];
+    let mut sites: Vec<String> = reciprocal["sites"].as_sequence().unwrap_or(&vec![])
+        .iter()
+        .filter_map(|v| v.as_str())
+        .map(|s| s.to_string())
+        .collect();
+    if sites.is_empty() {
+        sites = link_pages.iter().map(|page| page.link.clone()).collect();
+    }
+    
+    let mut paths: Vec<String> = reciprocal["paths"].as_sequence().unwrap_or(&vec![])
+        .iter()
+        .filter_map(|v| v.as_str())
+        .map(|s| s.to_string())
+        .collect();
+    if paths.is_empty() {
+        paths = ["/links/", "/friends/", "/link/", "/friend/", "/links.html"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+    }
+    
+    let reciprocal_link_pages = reciprocal["link_pages"].as_mapping()
+        .map(|mapping| {
+            mapping.iter()
+                .filter_map(|(k, v)| Some((k.as_str()?.to_string(), v.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+    
+    let reciprocal = ReciprocalConfig {
+        enable: reciprocal["enable"].as_bool().unwrap_or(false),
+        sites,
+        paths,
+        link_pages: reciprocal_link_pages,
+        concurrency: reciprocal["concurrency"].as_u64().filter(|n| *n > 0).unwrap_or(8) as usize,
+    };
+    
+    // 友链发现配置
+    let discovery = &yaml["DISCOVERY"];
+    let discovery = DiscoveryConfig {
+        enable: discovery["enable"].as_bool().unwrap_or(false),
+        depth: discovery["depth"].as_u64().unwrap_or(1) as usize,
+        max_sites: discovery["max_sites"].as_u64().unwrap_or(500) as usize,
+        min_links: discovery["min_links"].as_u64().unwrap_or(1) as usize,
+        concurrency: discovery["concurrency"].as_u64().filter(|n| *n > 0).unwrap_or(8) as usize,
+    };
+    
     Ok(FcSettings {
         enable_link_page,
         link_pages,
         settings_friends_links: FriendsLinksConfig {
             enable,
-            json_api_or_path,
+            sources,
             list,
         },
         block_sites,
         max_posts_num,
         outdate_clean,
+        output,
+        state,
+        health,
+        reciprocal,
+        discovery,
+        groups,
+        full_content,
+        content,
+        filters,
+        tagging,
+        http,
+        redirects,
+        proxy,
         // simple_mode已固化为true
     })
-}
\ No newline at end of file
+}
+
+/// 代理地址，环境变量 FC_PROXY 优先于配置文件中的地址
+fn proxy_url(configured: &str) -> String {
+    std::env::var("FC_PROXY")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| configured.to_string())
+        .trim()
+        .to_string()
+}
+
+/// 读取字符串列表，单个字符串视为只有一项的列表
+fn string_list(value: &serde_yaml::Value) -> Vec<String> {
+    match value {
+        serde_yaml::Value::String(s) => vec![s.to_string()],
+        _ => value.as_sequence().unwrap_or(&vec![])
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_string())
+            .collect(),
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn proxy(hosts: &[&str], bypass: &[&str]) -> ProxyConfig {
+        ProxyConfig {
+            url: String::from("socks5h://127.0.0.1:1080"),
+            hosts: hosts.iter().map(|h| h.to_string()).collect(),
+            bypass: bypass.iter().map(|h| h.to_string()).collect(),
+        }
+    }
+    
+    #[test]
+    fn empty_hosts_proxies_everything() {
+        let config = proxy(&[], &[]);
+        assert!(config.should_proxy("example.com"));
+        assert!(config.should_proxy("blog.example.org"));
+        assert!(!ProxyConfig::default().should_proxy("example.com"));
+    }
+    
+    #[test]
+    fn hosts_match_subdomains() {
+        let config = proxy(&["example.com", "*.github.io", ".medium.com"], &[]);
+        assert!(config.should_proxy("example.com"));
+        assert!(config.should_proxy("Blog.Example.com."));
+        assert!(config.should_proxy("someone.github.io"));
+        assert!(config.should_proxy("medium.com"));
+        assert!(!config.should_proxy("notexample.com"));
+        assert!(!config.should_proxy("example.org"));
+    }
+    
+    #[test]
+    fn bypass_wins_over_hosts() {
+        let config = proxy(&["example.com"], &["cn.example.com"]);
+        assert!(config.should_proxy("www.example.com"));
+        assert!(!config.should_proxy("cn.example.com"));
+        assert!(!config.should_proxy("img.cn.example.com"));
+        
+        let config = proxy(&[], &["localhost", "*.lan"]);
+        assert!(!config.should_proxy("nas.lan"));
+        assert!(config.should_proxy("example.com"));
+    }
+    
+    #[test]
+    fn env_overrides_configured_proxy() {
+        // 只有这一个测试读写 FC_PROXY
+        std::env::remove_var("FC_PROXY");
+        assert_eq!(proxy_url(" http://127.0.0.1:7890 "), "http://127.0.0.1:7890");
+        std::env::set_var("FC_PROXY", "socks5://10.0.0.1:1080");
+        assert_eq!(proxy_url("http://127.0.0.1:7890"), "socks5://10.0.0.1:1080");
+        std::env::set_var("FC_PROXY", "  ");
+        assert_eq!(proxy_url("http://127.0.0.1:7890"), "http://127.0.0.1:7890");
+        std::env::remove_var("FC_PROXY");
+    }
+}
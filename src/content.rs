@@ -0,0 +1,221 @@
+use std::borrow::Cow;
+use ammonia::{Builder, UrlRelative};
+use nipper::Document;
+use url::Url;
+use crate::config::ContentConfig;
+use crate::lang::detect_lang;
+use crate::models::PostMeta;
+
+/// 中日韩文字每分钟阅读字数
+const CJK_CHARS_PER_MINUTE: usize = 400;
+
+/// 拉丁字母等以空格分词的文字每分钟阅读词数
+const WORDS_PER_MINUTE: usize = 200;
+
+/// 常见的统计/跟踪像素地址
+const TRACKING_PATTERNS: [&str; 6] = [
+    "feeds.feedburner.com/~r/",
+    "feedsportal.com",
+    "pixel.wp.com",
+    "stats.wordpress.com",
+    "google-analytics.com",
+    "/pixel.gif",
+];
+
+/// 按配置处理文章正文：清理HTML、生成摘要、检测语言、统计字数，可选去掉全文
+pub fn process_post_content(post: &mut PostMeta, config: &ContentConfig) {
+    if config.sanitize {
+        post.content = sanitize_html(&post.content, &post.link);
+    }
+    
+    let text = html_to_text(&post.content);
+    // 订阅源只提供摘要、没有正文时保留原摘要，只按长度截取
+    post.summary = if text.is_empty() {
+        make_summary(post.summary.trim(), config.summary_length)
+    } else {
+        make_summary(&text, config.summary_length)
+    };
+    post.lang = detect_lang(&post.title, &text, &post.lang);
+    
+    let (cjk_chars, words) = count_words(&text);
+    post.word_count = cjk_chars + words;
+    post.reading_minutes = reading_minutes(cjk_chars, words);
+    
+    // 订阅源未提供封面时使用正文中的第一张图片
+    if post.cover.is_empty() {
+        post.cover = first_image(&post.content);
+    }
+    
+    if !config.include_content {
+        post.content = String::new();
+    }
+}
+
+/// 使用白名单清理HTML，去掉脚本、iframe与跟踪像素，并以文章链接为基准改写相对地址
+pub fn sanitize_html(html: &str, base: &str) -> String {
+    if html.is_empty() {
+        return String::new();
+    }
+    
+    let mut builder = Builder::default();
+    builder.attribute_filter(|element, attribute, value| {
+        if element == "img" && attribute == "src" && is_tracking_url(value) {
+            return None;
+        }
+        Some(Cow::Borrowed(value))
+    });
+    match Url::parse(base) {
+        Ok(base_url) => builder.url_relative(UrlRelative::RewriteWithBase(base_url)),
+        Err(_) => builder.url_relative(UrlRelative::Deny),
+    };
+    
+    builder.clean(html).to_string()
+}
+
+/// 判断是否为跟踪像素地址
+fn is_tracking_url(src: &str) -> bool {
+    TRACKING_PATTERNS.iter().any(|pattern| src.contains(pattern))
+}
+
+/// 正文中第一张非跟踪像素的图片地址
+fn first_image(html: &str) -> String {
+    if html.is_empty() {
+        return String::new();
+    }
+    
+    let doc = Document::from(format!("<body>{}</body>", html).as_str());
+    let images = doc.select("img");
+    for i in 0..images.length() {
+        if let Some(src) = images.get(i).and_then(|img| img.attr("src")) {
+            let src = src.trim();
+            if !src.is_empty() && !src.starts_with("data:") && !is_tracking_url(src) {
+                return src.to_string();
+            }
+        }
+    }
+    
+    String::new()
+}
+
+/// 提取HTML中的纯文本，去掉脚本与样式并合并连续空白
+pub fn html_to_text(html: &str) -> String {
+    if html.is_empty() {
+        return String::new();
+    }
+    
+    let doc = Document::from(format!("<body>{}</body>", html).as_str());
+    doc.select("script, style, noscript, template").remove();
+    let text = doc.select("body").text();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 统计纯文本字数，返回 (中日韩字符数, 其他文字的词数)
+/// 中日韩文字逐字计数，其余连续的字母数字按一个词计数
+pub fn count_words(text: &str) -> (usize, usize) {
+    let mut cjk_chars = 0;
+    let mut words = 0;
+    let mut in_word = false;
+    
+    for c in text.chars() {
+        if is_cjk(c) {
+            cjk_chars += 1;
+            in_word = false;
+        } else if c.is_alphanumeric() {
+            if !in_word {
+                words += 1;
+                in_word = true;
+            }
+        } else if !(in_word && (c == '\'' || c == '’' || c == '-')) {
+            // 词中的撇号与连字符不拆分单词，如 don't、well-known
+            in_word = false;
+        }
+    }
+    
+    (cjk_chars, words)
+}
+
+/// 预计阅读分钟数，有内容时至少为1
+pub fn reading_minutes(cjk_chars: usize, words: usize) -> usize {
+    if cjk_chars == 0 && words == 0 {
+        return 0;
+    }
+    let minutes = cjk_chars as f64 / CJK_CHARS_PER_MINUTE as f64 + words as f64 / WORDS_PER_MINUTE as f64;
+    (minutes.ceil() as usize).max(1)
+}
+
+/// 是否为中日韩文字（汉字、假名、谚文）
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // 平假名、片假名
+        | 0x3400..=0x4DBF   // 汉字扩展A
+        | 0x4E00..=0x9FFF   // 基本汉字
+        | 0xAC00..=0xD7AF   // 谚文音节
+        | 0xF900..=0xFAFF   // 兼容汉字
+        | 0x20000..=0x2FA1F // 汉字扩展B及以后
+    )
+}
+
+/// 截取指定字符数的摘要，超出时以省略号结尾，长度为0时不生成摘要
+pub fn make_summary(text: &str, length: usize) -> String {
+    if length == 0 {
+        return String::new();
+    }
+    if text.chars().count() <= length {
+        return text.to_string();
+    }
+    
+    let mut summary: String = text.chars().take(length).collect();
+    summary = summary.trim_end().to_string();
+    summary.push('…');
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn post(content: &str, summary: &str) -> PostMeta {
+        PostMeta {
+            title: String::from("标题"),
+            link: String::from("https://example.com/post/1"),
+            created: String::new(),
+            updated: String::new(),
+            content: content.to_string(),
+            summary: summary.to_string(),
+            guid: String::new(),
+            tags: Vec::new(),
+            cover: String::new(),
+            enclosures: Vec::new(),
+            lang: String::new(),
+            word_count: 0,
+            reading_minutes: 0,
+        }
+    }
+    
+    #[test]
+    fn keeps_feed_summary_without_content() {
+        let config = ContentConfig { sanitize: true, summary_length: 6, include_content: true };
+        let mut only_summary = post("", " 订阅源提供的摘要文字 ");
+        process_post_content(&mut only_summary, &config);
+        assert_eq!(only_summary.summary, "订阅源提供的…");
+        
+        let config = ContentConfig { summary_length: 0, ..config };
+        let mut no_summary = post("", "摘要");
+        process_post_content(&mut no_summary, &config);
+        assert_eq!(no_summary.summary, "");
+    }
+    
+    #[test]
+    fn counts_mixed_cjk_and_latin() {
+        assert_eq!(count_words("我用Rust写了一个爬虫，don't well-known test."), (8, 4));
+        assert_eq!(count_words("  ，。 "), (0, 0));
+    }
+    
+    #[test]
+    fn reading_minutes_rounds_up() {
+        assert_eq!(reading_minutes(0, 0), 0);
+        assert_eq!(reading_minutes(8, 4), 1);
+        assert_eq!(reading_minutes(400, 200), 2);
+        assert_eq!(reading_minutes(401, 0), 2);
+    }
+}
@@ -2,93 +2,91 @@ use std::collections::HashMap;
 use chrono::{DateTime, FixedOffset, Utc};
 use nipper::Document;
 use reqwest::Client;
+use reqwest::header::HeaderMap;
 use url::Url;
-use crate::models::{Friends, PostMeta};
+use crate::feed::parse_feed;
+use crate::config::HttpConfig;
+use crate::error::Result;
+use crate::fetch::{fetch_document, fetch_text, resolve_redirect, ContentKind};
+use crate::models::{Friends, PostMeta, Redirect};
 
 const BEIJING_OFFSET: Option<FixedOffset> = FixedOffset::east_opt(8 * 3600);
 
+/// 文章列表，以及友链主页与订阅源经过重定向后的地址
+#[derive(Debug, Clone, Default)]
+pub struct PostPage {
+    pub posts: Vec<PostMeta>,
+    pub link_redirect: Redirect,
+    pub feed_redirect: Redirect,
+}
+
 /// 爬取友链页面，获取作者、链接、头像信息
 pub async fn crawl_link_page(
     link_page: &str,
     css_rules: &serde_yaml::Value,
+    http: &HttpConfig,
     client: &Client,
-) -> Result<Vec<Friends>, Box<dyn std::error::Error>> {
-    let response = client.get(link_page).send().await?;
-    let html = response.text().await?;
+) -> Result<Vec<Friends>> {
+    let html = fetch_text(client, link_page, &HeaderMap::new(), ContentKind::Html, http).await?;
     
     let doc = Document::from(&html);
     let mut friends_list = Vec::new();
     
-    // 尝试不同主题的CSS选择器规则
-    if let Some(theme_rules) = css_rules.get("link_page_rules") {
-        for (_theme, rules) in theme_rules.as_mapping().unwrap_or(&serde_yaml::Mapping::new()) {
-            if let Some(author_selector) = rules["author"].as_str() {
-                    let authors = doc.select(author_selector);
-                
-                if authors.length() > 0 {
-                    // 找到匹配的主题规则
-                    let link_selector = rules["link"].as_str().unwrap_or(".friend-name a");
-                    let avatar_selector = rules["avatar"].as_str().unwrap_or(".avatar img");
-                    
-                    let links = doc.select(link_selector);
-                    let avatars = doc.select(avatar_selector);
-                    
-                    let now = Utc::now().with_timezone(&BEIJING_OFFSET.unwrap());
-                    let created_at = now.format("%Y-%m-%d %H:%M:%S").to_string();
-                    
-                    // 合并数据
-                    let mut author_map = HashMap::new();
-                    for i in 0..authors.length() {
-                        if let Some(author) = authors.get(i) {
-                            author_map.insert(i, decode_html_entities(author.text().trim()));
-                        }
-                    }
-                    
-                    let mut link_map = HashMap::new();
-                    for i in 0..links.length() {
-                        if let Some(link) = links.get(i) {
-                            if let Some(href) = link.attr("href") {
-                                if let Ok(full_url) = resolve_relative_url(&href, &link_page) {
-                                    link_map.insert(i, full_url);
-                                }
-                            }
-                        }
-                    }
-                    
-                    let mut avatar_map = HashMap::new();
-                    for i in 0..avatars.length() {
-                        if let Some(avatar) = avatars.get(i) {
-                            if let Some(src) = avatar.attr("src") {
-                                if let Ok(full_url) = resolve_relative_url(&src, &link_page) {
-                                    avatar_map.insert(i, full_url);
-                                }
-                            }
-                        }
-                    }
-                    
-                    // 创建Friends对象
-                    let max_index = std::cmp::max(
-                        std::cmp::max(author_map.len(), link_map.len()),
-                        avatar_map.len()
-                    );
-                    
-                    for i in 0..max_index {
-                        let name = author_map.get(&i).unwrap_or(&String::from("Unknown")).clone();
-                        let link = link_map.get(&i).unwrap_or(&String::from(link_page)).clone();
-                        let avatar = avatar_map.get(&i).unwrap_or(&String::from("")).clone();
-                        
-                        friends_list.push(Friends {
-                            name,
-                            link,
-                            avatar,
-                            error: false,
-                            created_at: created_at.clone(),
-                        });
-                    }
-                    
-                    break; // 找到匹配的规则后停止尝试
+    // 尝试不同主题的CSS选择器规则，兼容传入整份规则或仅link_page_rules部分
+    let theme_rules = css_rules.get("link_page_rules").unwrap_or(css_rules);
+    for (_theme, rules) in theme_rules.as_mapping().unwrap_or(&serde_yaml::Mapping::new()) {
+        let authors = select_by_rule(&doc, &rules["author"], "text");
+        
+        if !authors.is_empty() {
+            // 找到匹配的主题规则
+            let links = select_by_rule(&doc, &rule_or(&rules["link"], ".friend-name a"), "href");
+            let avatars = select_by_rule(&doc, &rule_or(&rules["avatar"], ".avatar img"), "src");
+            
+            let now = Utc::now().with_timezone(&BEIJING_OFFSET.unwrap());
+            let created_at = now.format("%Y-%m-%d %H:%M:%S").to_string();
+            
+            // 合并数据，nipper 取出的文本已经解码过实体
+            let mut author_map = HashMap::new();
+            for (i, author) in authors.iter().enumerate() {
+                author_map.insert(i, author.clone());
+            }
+            
+            let mut link_map = HashMap::new();
+            for (i, href) in links.iter().enumerate() {
+                if let Ok(full_url) = resolve_relative_url(href, link_page) {
+                    link_map.insert(i, full_url);
                 }
             }
+            
+            let mut avatar_map = HashMap::new();
+            for (i, src) in avatars.iter().enumerate() {
+                if let Ok(full_url) = resolve_relative_url(src, link_page) {
+                    avatar_map.insert(i, full_url);
+                }
+            }
+            
+            // 创建Friends对象
+            let max_index = std::cmp::max(
+                std::cmp::max(author_map.len(), link_map.len()),
+                avatar_map.len()
+            );
+            
+            for i in 0..max_index {
+                let name = author_map.get(&i).unwrap_or(&String::from("Unknown")).clone();
+                let link = link_map.get(&i).unwrap_or(&String::from(link_page)).clone();
+                let avatar = avatar_map.get(&i).unwrap_or(&String::from("")).clone();
+                
+                friends_list.push(Friends {
+                    name,
+                    link,
+                    avatar,
+                    group: String::new(),
+                    error: false,
+                    created_at: created_at.clone(),
+                });
+            }
+            
+            break; // 找到匹配的规则后停止尝试
         }
     }
     
@@ -96,118 +94,256 @@ pub async fn crawl_link_page(
 }
 
 /// 爬取文章页面或RSS，获取文章列表
+/// max_posts 为友链或分组单独设置的文章数上限：订阅源只在有单独设置时截断，HTML页面未设置时使用 MAX_POSTS_NUM
+/// headers 为友链单独设置的请求头
 pub async fn crawl_post_page(
     link: &str,
     fc_settings: &crate::config::FcSettings,
     custom_rss: &str,
+    max_posts: Option<usize>,
     css_rules: &serde_yaml::Value,
+    headers: &HeaderMap,
     client: &Client,
-) -> Result<Vec<PostMeta>, Box<dyn std::error::Error>> {
-    // 如果提供了自定义RSS，则直接爬取RSS
-    if !custom_rss.is_empty() {
-        return crawl_post_page_feed(custom_rss, client).await;
+) -> Result<PostPage> {
+    // 如果提供了自定义RSS或链接本身就是RSS，则直接爬取RSS
+    let feed_url = feed_url_for(link, custom_rss);
+    if !feed_url.is_empty() {
+        let (mut posts, feed_redirect) = crawl_post_page_feed(&feed_url, &fc_settings.http, headers, client).await?;
+        if let Some(max_posts) = max_posts.filter(|n| *n > 0) {
+            posts.truncate(max_posts);
+        }
+        
+        // 主页没有被抓取，单独检查其重定向；订阅源地址就是友链地址时无需再次请求
+        let link_redirect = if feed_url == link {
+            feed_redirect.clone()
+        } else if fc_settings.redirects.enable {
+            resolve_redirect(client, link, headers).await.unwrap_or_else(|e| {
+                tracing::debug!("Failed to check redirects of {}: {}", link, e);
+                Redirect::default()
+            })
+        } else {
+            Redirect::default()
+        };
+        return Ok(PostPage { posts, link_redirect, feed_redirect });
     }
     
-    // 否则尝试作为RSS链接爬取
-    if link.ends_with(".xml") || link.ends_with(".rss") || link.contains("feed") {
-        return crawl_post_page_feed(link, client).await;
+    // 最后尝试爬取HTML页面，相对链接按重定向后的地址解析
+    let (html, link_redirect) = fetch_document(client, link, headers, ContentKind::Html, &fc_settings.http).await?;
+    
+    let (mut posts, content_rule) = parse_post_page(&html, &link_redirect.final_url, css_rules);
+    
+    // 限制文章数量
+    let max_posts = max_posts.unwrap_or(fc_settings.max_posts_num);
+    if max_posts > 0 && posts.len() > max_posts {
+        posts.truncate(max_posts);
     }
     
-    // 最后尝试爬取HTML页面
-    let response = client.get(link).send().await?;
-    let html = response.text().await?;
+    // 可选：逐篇抓取文章页面获取正文
+    if fc_settings.full_content.enable {
+        fill_post_contents(&mut posts, &content_rule, fc_settings.full_content.concurrency, &fc_settings.http, headers, client).await;
+    }
     
-    let doc = Document::from(&html);
+    Ok(PostPage { posts, link_redirect, feed_redirect: Redirect::default() })
+}
+
+/// 按文章页规则解析HTML主页，返回文章列表和匹配主题的正文规则
+fn parse_post_page(html: &str, link: &str, css_rules: &serde_yaml::Value) -> (Vec<PostMeta>, serde_yaml::Value) {
+    let doc = Document::from(html);
     let mut posts = Vec::new();
+    let mut content_rule = serde_yaml::Value::Null;
     
-    // 尝试不同主题的CSS选择器规则
-    if let Some(theme_rules) = css_rules.get("post_page_rules") {
-        for (_theme, rules) in theme_rules.as_mapping().unwrap_or(&serde_yaml::Mapping::new()) {
-            if let Some(title_selector) = rules["title"].as_str() {
-                    let titles = doc.select(title_selector);
+    // 页面声明的语言，如 <html lang="zh-CN">
+    let page_lang = doc.select("html").attr("lang").map(|lang| lang.to_string()).unwrap_or_default();
+    
+    // 尝试不同主题的CSS选择器规则，兼容传入整份规则或仅post_page_rules部分
+    let theme_rules = css_rules.get("post_page_rules").unwrap_or(css_rules);
+    for (_theme, rules) in theme_rules.as_mapping().unwrap_or(&serde_yaml::Mapping::new()) {
+        let titles = select_by_rule(&doc, &rules["title"], "text");
+        
+        if !titles.is_empty() {
+            // 找到匹配的主题规则
+            let links = select_by_rule(&doc, &rule_or(&rules["link"], ".article-title a"), "href");
+            let created_times = select_by_rule(&doc, &rule_or(&rules["created"], ".article-date"), "text");
+            content_rule = rules["content"].clone();
+            
+            // 合并数据，nipper 取出的文本已经解码过实体
+            let mut title_map = HashMap::new();
+            for (i, title) in titles.iter().enumerate() {
+                title_map.insert(i, title.clone());
+            }
+            
+            let mut link_map = HashMap::new();
+            for (i, href) in links.iter().enumerate() {
+                if let Ok(full_url) = resolve_relative_url(href, link) {
+                    link_map.insert(i, full_url);
+                }
+            }
+            
+            let mut time_map = HashMap::new();
+            for (i, time) in created_times.iter().enumerate() {
+                time_map.insert(i, clean_time_string(time));
+            }
+            
+            // 创建PostMeta对象
+            let max_index = std::cmp::max(
+                std::cmp::max(title_map.len(), link_map.len()),
+                time_map.len()
+            );
+            
+            for i in 0..max_index {
+                let title = title_map.get(&i).unwrap_or(&String::from("Untitled")).clone();
+                let post_link = link_map.get(&i).unwrap_or(&String::from(link)).clone();
+                let created = time_map.get(&i).unwrap_or(&String::from("")).clone();
                 
-                if titles.length() > 0 {
-                    // 找到匹配的主题规则
-                    let link_selector = rules["link"].as_str().unwrap_or(".article-title a");
-                    let created_selector = rules["created"].as_str().unwrap_or(".article-date");
-                    
-                    let links = doc.select(link_selector);
-                    let created_times = doc.select(created_selector);
-                    
-                    // 合并数据
-                    let mut title_map = HashMap::new();
-                    for i in 0..titles.length() {
-                        if let Some(title) = titles.get(i) {
-                            title_map.insert(i, decode_html_entities(title.text().trim()));
-                        }
-                    }
-                    
-                    let mut link_map = HashMap::new();
-                    for i in 0..links.length() {
-                        if let Some(link_elem) = links.get(i) {
-                            if let Some(href) = link_elem.attr("href") {
-                                if let Ok(full_url) = resolve_relative_url(&href, link) {
-                                    link_map.insert(i, full_url);
-                                }
-                            }
-                        }
-                    }
-                    
-                    let mut time_map = HashMap::new();
-                    for i in 0..created_times.length() {
-                        if let Some(time_elem) = created_times.get(i) {
-                            let time_str = clean_time_string(time_elem.text().trim());
-                            time_map.insert(i, time_str);
-                        }
-                    }
-                    
-                    // 创建PostMeta对象
-                    let max_index = std::cmp::max(
-                        std::cmp::max(title_map.len(), link_map.len()),
-                        time_map.len()
-                    );
-                    
-                    for i in 0..max_index {
-                        let title = title_map.get(&i).unwrap_or(&String::from("Untitled")).clone();
-                        let post_link = link_map.get(&i).unwrap_or(&String::from(link)).clone();
-                        let created = time_map.get(&i).unwrap_or(&String::from("")).clone();
-                        
-                        // 对于HTML页面，暂时使用空字符串作为内容
-                        // 在实际应用中，可以进一步爬取每个文章链接获取详细内容
-                        let content = String::new();
-                        
-                        posts.push(PostMeta {
-                            title,
-                            link: post_link,
-                            created: created.clone(),
-                            updated: created,
-                            content, // 添加文章正文内容
-                        });
-                    }
-                    
-                    break; // 找到匹配的规则后停止尝试
+                // 正文在开启全文抓取时由 fill_post_contents 填充
+                let content = String::new();
+                
+                posts.push(PostMeta {
+                    title,
+                    link: post_link,
+                    created: created.clone(),
+                    updated: created,
+                    content, // 添加文章正文内容
+                    summary: String::new(),
+                    guid: String::new(),
+                    tags: Vec::new(),
+                    cover: String::new(),
+                    enclosures: Vec::new(),
+                    lang: page_lang.clone(),
+                    word_count: 0,
+                    reading_minutes: 0,
+                });
+            }
+            
+            break; // 找到匹配的规则后停止尝试
+        }
+    }
+    
+    (posts, content_rule)
+}
+
+/// 并发抓取文章页面，填充正文内容，单篇失败时保留空正文
+async fn fill_post_contents(
+    posts: &mut [PostMeta],
+    content_rule: &serde_yaml::Value,
+    concurrency: usize,
+    http: &HttpConfig,
+    headers: &HeaderMap,
+    client: &Client,
+) {
+    for batch in posts.chunks_mut(concurrency.max(1)) {
+        let mut tasks = Vec::new();
+        for post in batch.iter() {
+            let post_link = post.link.clone();
+            let content_rule_clone = content_rule.clone();
+            let http_clone = http.clone();
+            let headers_clone = headers.clone();
+            let client_clone = client.clone();
+            tasks.push(tokio::task::spawn(async move {
+                crawl_post_content(&post_link, &content_rule_clone, &http_clone, &headers_clone, &client_clone).await
+            }));
+        }
+        
+        for (post, task) in batch.iter_mut().zip(tasks) {
+            match task.await {
+                Ok(Ok(content)) => post.content = content,
+                Ok(Err(e)) => tracing::warn!("Failed to fetch content of {}: {}", post.link, e),
+                Err(e) => tracing::warn!("Content task for {} failed: {}", post.link, e),
+            }
+        }
+    }
+}
+
+/// 抓取单篇文章页面并提取正文HTML
+async fn crawl_post_content(
+    post_link: &str,
+    content_rule: &serde_yaml::Value,
+    http: &HttpConfig,
+    headers: &HeaderMap,
+    client: &Client,
+) -> Result<String> {
+    let html = fetch_text(client, post_link, headers, ContentKind::Html, http).await?;
+    Ok(extract_main_content(&html, content_rule))
+}
+
+/// 提取正文：优先使用主题的content规则，否则在常见正文容器中选取文本最长者
+fn extract_main_content(html: &str, content_rule: &serde_yaml::Value) -> String {
+    let doc = Document::from(html);
+    
+    let contents = select_by_rule(&doc, content_rule, "html");
+    if let Some(content) = contents.into_iter().find(|c| !c.is_empty()) {
+        return content;
+    }
+    
+    let candidates = [
+        "[itemprop='articleBody']",
+        "article .post-content",
+        "article .entry-content",
+        ".post-content",
+        ".entry-content",
+        ".article-content",
+        ".post-body",
+        ".markdown-body",
+        "#article-container",
+        "article",
+        "main",
+    ];
+    
+    let mut best = String::new();
+    let mut best_len = 0;
+    for selector in candidates {
+        let selection = doc.select(selector);
+        for i in 0..selection.length() {
+            if let Some(node) = selection.get(i) {
+                let text_len = node.text().trim().chars().count();
+                if text_len > best_len {
+                    best_len = text_len;
+                    best = node.html().to_string();
                 }
             }
         }
     }
     
-    // 限制文章数量
-    if fc_settings.max_posts_num > 0 && posts.len() > fc_settings.max_posts_num {
-        posts.truncate(fc_settings.max_posts_num);
+    best
+}
+
+/// 推断实际爬取的订阅源地址，需要抓取HTML页面时返回空字符串
+pub fn feed_url_for(link: &str, custom_rss: &str) -> String {
+    if !custom_rss.is_empty() {
+        return custom_rss.to_string();
+    }
+    
+    if link.ends_with(".xml") || link.ends_with(".rss") || link.contains("feed") {
+        return link.to_string();
     }
     
-    Ok(posts)
+    String::new()
 }
 
-/// 爬取RSS订阅源
+/// 爬取订阅源，XML无法严格解析时退回宽松的HTML解析
+/// 同时返回订阅源经过重定向后的地址，文章中的相对链接按该地址解析
 pub async fn crawl_post_page_feed(
     feed_url: &str,
+    http: &HttpConfig,
+    headers: &HeaderMap,
     client: &Client,
-) -> Result<Vec<PostMeta>, Box<dyn std::error::Error>> {
-    let response = client.get(feed_url).send().await?;
-    let xml = response.text().await?;
+) -> Result<(Vec<PostMeta>, Redirect)> {
+    let (xml, redirect) = fetch_document(client, feed_url, headers, ContentKind::Feed, http).await?;
+    let base = redirect.final_url.as_str();
     
-    let doc = Document::from(&xml);
+    let posts = match parse_feed(&xml, base) {
+        Ok(posts) => posts,
+        Err(e) => {
+            tracing::warn!("Failed to parse feed {} strictly, falling back: {}", feed_url, e);
+            parse_feed_loose(&xml, base)
+        }
+    };
+    Ok((posts, redirect))
+}
+
+/// 使用HTML解析器宽松解析RSS，用于格式不规范的订阅源
+fn parse_feed_loose(xml: &str, feed_url: &str) -> Vec<PostMeta> {
+    let doc = Document::from(xml);
     let mut posts = Vec::new();
     
     // 尝试RSS格式
@@ -247,7 +383,7 @@ pub async fn crawl_post_page_feed(
         for content_tag in ["content:encoded", "description"] {
             let content_elem = items.select(&format!("item:nth-child({}) {} ", i + 1, content_tag));
             if content_elem.length() > 0 {
-                content = decode_html_entities(content_elem.text().trim());
+                content = content_elem.text().trim().to_string();
                 if !content.is_empty() {
                     break;
                 }
@@ -256,20 +392,81 @@ pub async fn crawl_post_page_feed(
         
         if let Ok(resolved_link) = resolve_relative_url(&link, feed_url) {
             posts.push(PostMeta {
-                title: decode_html_entities(&title),
+                title,
                 link: resolved_link,
                 created: created.clone(),
                 updated: created,
                 content, // 添加文章正文内容
+                summary: String::new(),
+                guid: String::new(),
+                tags: Vec::new(),
+                cover: String::new(),
+                enclosures: Vec::new(),
+                lang: String::new(),
+                word_count: 0,
+                reading_minutes: 0,
             });
         }
     }
     
-    Ok(posts)
+    posts
+}
+
+/// 按CSS规则提取页面中的值
+/// 规则可以是单个选择器字符串，也可以是依次尝试的 {selector, attr} 列表
+/// attr 为 text 时取文本，html 时取元素HTML，time 时优先取 datetime 属性
+fn select_by_rule(doc: &Document, rule: &serde_yaml::Value, default_attr: &str) -> Vec<String> {
+    let candidates: Vec<(&str, &str)> = match rule {
+        serde_yaml::Value::String(selector) => vec![(selector.as_str(), default_attr)],
+        serde_yaml::Value::Sequence(items) => items
+            .iter()
+            .filter_map(|item| {
+                let selector = item["selector"].as_str()?;
+                Some((selector, item["attr"].as_str().unwrap_or(default_attr)))
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    
+    for (selector, attr) in candidates {
+        let selection = doc.select(selector);
+        let mut values = Vec::new();
+        for i in 0..selection.length() {
+            if let Some(node) = selection.get(i) {
+                let value = match attr {
+                    "text" => Some(node.text().trim().to_string()),
+                    "html" => Some(node.html().trim().to_string()),
+                    // time 优先取 datetime 属性，没有时取文本
+                    "time" => node.attr("datetime")
+                        .map(|v| v.trim().to_string())
+                        .or_else(|| Some(node.text().trim().to_string())),
+                    _ => node.attr(attr).map(|v| v.trim().to_string()),
+                };
+                if let Some(value) = value {
+                    values.push(value);
+                }
+            }
+        }
+        
+        if !values.is_empty() {
+            return values;
+        }
+    }
+    
+    Vec::new()
+}
+
+/// 规则缺失时使用默认选择器
+fn rule_or(rule: &serde_yaml::Value, default_selector: &str) -> serde_yaml::Value {
+    if rule.is_null() {
+        serde_yaml::Value::String(default_selector.to_string())
+    } else {
+        rule.clone()
+    }
 }
 
 /// 解析RSS时间格式
-fn parse_rss_time(time_str: &str) -> String {
+pub fn parse_rss_time(time_str: &str) -> String {
     // 尝试多种时间格式
     let formats = [
         "%a, %d %b %Y %H:%M:%S %z",
@@ -321,7 +518,7 @@ fn clean_time_string(time_str: &str) -> String {
 }
 
 /// 解析相对URL为绝对URL
-fn resolve_relative_url(relative: &str, base: &str) -> Result<String, Box<dyn std::error::Error>> {
+pub fn resolve_relative_url(relative: &str, base: &str) -> std::result::Result<String, url::ParseError> {
     if relative.starts_with("http://") || relative.starts_with("https://") {
         return Ok(relative.to_string());
     }
@@ -331,14 +528,38 @@ fn resolve_relative_url(relative: &str, base: &str) -> Result<String, Box<dyn st
     Ok(joined.to_string())
 }
 
+/// 提取站点主机名，忽略大小写与 www. 前缀，用于判断是否同一站点
+pub fn site_host(link: &str) -> Option<String> {
+    let url = Url::parse(link).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    Some(host.strip_prefix("www.").unwrap_or(&host).to_string())
+}
+
 /// 解码HTML实体
-fn decode_html_entities(text: &str) -> String {
-    // 简单的HTML实体解码
-    let mut result = text.to_string();
-    result = result.replace("&lt;", "<");
-    result = result.replace("&gt;", ">");
-    result = result.replace("&amp;", "&");
-    result = result.replace("&quot;", "\"");
-    result = result.replace("&#39;", "'");
-    result
-}
\ No newline at end of file
+/// 一次遍历解码全部HTML5命名实体及十进制、十六进制数字引用，避免 &amp;lt; 被重复解码
+pub fn decode_html_entities(text: &str) -> String {
+    html_escape::decode_html_entities(text).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn decodes_named_entities() {
+        assert_eq!(decode_html_entities("Rust&nbsp;&mdash;&nbsp;笔记&hellip;"), "Rust\u{a0}—\u{a0}笔记…");
+        assert_eq!(decode_html_entities("Tom &amp; Jerry &lt;3"), "Tom & Jerry <3");
+    }
+    
+    #[test]
+    fn decodes_numeric_entities() {
+        assert_eq!(decode_html_entities("2023 &#8211; 2024"), "2023 – 2024");
+        assert_eq!(decode_html_entities("&#x4E2D;&#X6587;&#8217;s"), "中文’s");
+    }
+    
+    #[test]
+    fn decodes_escaped_ampersand_once() {
+        assert_eq!(decode_html_entities("&amp;lt;div&amp;gt;"), "&lt;div&gt;");
+        assert_eq!(decode_html_entities("&amp;#8211;"), "&#8211;");
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use reqwest::Client;
+use tokio::sync::Semaphore;
+use tokio::task;
+use crate::config::{CssRules, FcSettings};
+use crate::crawler::site_host;
+use crate::downloader::{find_link_page, is_blocked_site, link_page_candidates};
+use crate::models::{DiscoveredSite, DiscoveryEdge, DiscoveryReport, FriendSuggestion, Friends};
+
+/// 从我们的友链出发，逐层读取各站点的友链页，构建博客互链图并推荐新友链
+pub async fn start_discovery(
+    friends: &[Friends],
+    fc_settings: &FcSettings,
+    css_rules: &CssRules,
+    client: &Client,
+    last_updated_time: String,
+) -> DiscoveryReport {
+    let config = &fc_settings.discovery;
+    let own_hosts: HashSet<String> = fc_settings.reciprocal.sites
+        .iter()
+        .filter_map(|site| site_host(site))
+        .collect();
+    let friend_hosts: HashSet<String> = friends
+        .iter()
+        .filter_map(|friend| site_host(&friend.link))
+        .collect();
+    
+    // 图中的站点与边，均以主机名为键
+    let mut sites: HashMap<String, DiscoveredSite> = HashMap::new();
+    let mut edges: HashSet<(String, String)> = HashSet::new();
+    let mut visited: HashSet<String> = own_hosts.clone();
+    let mut frontier: Vec<Friends> = Vec::new();
+    
+    for friend in friends {
+        if is_blocked_site(&friend.link, &fc_settings.block_sites) {
+            continue;
+        }
+        if let Some(host) = site_host(&friend.link) {
+            sites.entry(host).or_insert_with(|| DiscoveredSite {
+                name: friend.name.clone(),
+                link: friend.link.clone(),
+                avatar: friend.avatar.clone(),
+                depth: 0,
+            });
+            frontier.push(friend.clone());
+        }
+    }
+    
+    // 限制同时读取友链页的站点数，每层最多可能有 max_sites 个站点
+    let semaphore = Arc::new(Semaphore::new(config.concurrency.max(1)));
+    
+    for depth in 1..=config.depth {
+        let mut tasks = Vec::new();
+        
+        for site in frontier.drain(..) {
+            let host = match site_host(&site.link) {
+                Some(host) => host,
+                None => continue,
+            };
+            if visited.len() >= config.max_sites + own_hosts.len() {
+                tracing::warn!("Discovery reached max_sites limit {}", config.max_sites);
+                break;
+            }
+            if !visited.insert(host.clone()) {
+                continue;
+            }
+            
+            let candidates = link_page_candidates(&site.link, fc_settings);
+            let link_page_rules = css_rules.link_page_rules.clone();
+            let http_clone = fc_settings.http.clone();
+            let client_clone = client.clone();
+            let semaphore_clone = semaphore.clone();
+            
+            tasks.push(task::spawn(async move {
+                let _permit = semaphore_clone.acquire_owned().await;
+                let found = find_link_page(&candidates, &link_page_rules, &http_clone, &client_clone).await;
+                (host, found)
+            }));
+        }
+        
+        let mut next_frontier = Vec::new();
+        for task in tasks {
+            let (from, found) = match task.await {
+                Ok((from, Some((_, found)))) => (from, found),
+                Ok(_) => continue,
+                Err(e) => {
+                    tracing::error!("Discovery task failed: {}", e);
+                    continue;
+                }
+            };
+            
+            for friend in found {
+                if is_blocked_site(&friend.link, &fc_settings.block_sites) {
+                    continue;
+                }
+                let to = match site_host(&friend.link) {
+                    Some(host) if host != from => host,
+                    _ => continue,
+                };
+                
+                sites.entry(to.clone()).or_insert_with(|| DiscoveredSite {
+                    name: friend.name.clone(),
+                    link: friend.link.clone(),
+                    avatar: friend.avatar.clone(),
+                    depth,
+                });
+                edges.insert((from.clone(), to.clone()));
+                if !visited.contains(&to) {
+                    next_frontier.push(friend);
+                }
+            }
+        }
+        
+        tracing::info!("Discovery depth {}: {} sites, {} links", depth, sites.len(), edges.len());
+        frontier = next_frontier;
+    }
+    
+    // 按被我们的友链链接的次数排序推荐
+    let mut suggestions: Vec<FriendSuggestion> = sites
+        .iter()
+        .filter(|(host, _)| !friend_hosts.contains(*host) && !own_hosts.contains(*host))
+        .filter_map(|(host, site)| {
+            let mut linked_by: Vec<String> = edges
+                .iter()
+                .filter(|(from, to)| to == host && friend_hosts.contains(from))
+                .filter_map(|(from, _)| sites.get(from).map(|s| s.name.clone()))
+                .collect();
+            linked_by.sort();
+            let inbound_num = edges.iter().filter(|(_, to)| to == host).count();
+            
+            if linked_by.len() < config.min_links {
+                return None;
+            }
+            Some(FriendSuggestion {
+                name: site.name.clone(),
+                link: site.link.clone(),
+                avatar: site.avatar.clone(),
+                linked_by_num: linked_by.len(),
+                inbound_num,
+                linked_by,
+            })
+        })
+        .collect();
+    suggestions.sort_by(|a, b| {
+        b.linked_by_num.cmp(&a.linked_by_num)
+            .then(b.inbound_num.cmp(&a.inbound_num))
+            .then(a.link.cmp(&b.link))
+    });
+    
+    let mut edges: Vec<DiscoveryEdge> = edges
+        .into_iter()
+        .map(|(from, to)| DiscoveryEdge { from, to })
+        .collect();
+    edges.sort_by(|a, b| a.from.cmp(&b.from).then(a.to.cmp(&b.to)));
+    
+    let mut sites: Vec<DiscoveredSite> = sites.into_values().collect();
+    sites.sort_by(|a, b| a.depth.cmp(&b.depth).then(a.link.cmp(&b.link)));
+    
+    DiscoveryReport {
+        site_num: sites.len(),
+        edge_num: edges.len(),
+        last_updated_time,
+        suggestions,
+        sites,
+        edges,
+    }
+}
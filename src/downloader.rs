@@ -1,19 +1,86 @@
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
-use reqwest::Client;
-use crate::config::{FcSettings, CssRules};
-use crate::crawler::{crawl_link_page, crawl_post_page};
-use crate::models::{Friends, PostMeta};
+use reqwest::{Client, ClientBuilder, Proxy};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::redirect::Policy;
+use tokio::sync::Semaphore;
+use tokio::task;
+use url::Url;
+use crate::canonical::{clean_url, dedupe_friends};
+use crate::config::{FcSettings, CssRules, FriendEntry, FriendSource, HttpConfig, ProxyConfig};
+use crate::crawler::{crawl_link_page, crawl_post_page, site_host, PostPage};
+use crate::error::{BlockReason, Error, Result};
+use crate::fetch::{fetch_text, ContentKind};
+use crate::friend_list::{extract_friends, parse_document};
+use crate::models::{Friends, ReciprocalResult, ReciprocalStatus};
 
-/// 构建HTTP客户端
-pub fn build_client(timeout: u64, _retry_count: u32) -> Client {
-    Client::builder()
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36";
+
+/// 构建HTTP客户端，重定向由 fetch 模块手动跟随
+pub fn build_client(timeout: u64, _retry_count: u32, proxy: &ProxyConfig) -> Client {
+    let builder = Client::builder()
         .timeout(Duration::from_secs(timeout))
         .connect_timeout(Duration::from_secs(5))
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36")
+        .user_agent(DEFAULT_USER_AGENT)
+        .redirect(Policy::none());
+    with_proxy(builder, proxy)
         .build()
         .expect("Failed to build HTTP client")
 }
 
+/// 为单独设置了超时或UA的友链构建HTTP客户端
+pub fn build_friend_client(entry: &FriendEntry, default_timeout: u64, proxy: &ProxyConfig) -> Client {
+    // UA 不是合法的请求头值（如含中文或换行）时客户端无法构建，与无效请求头一样忽略
+    let user_agent = if entry.user_agent.is_empty() {
+        DEFAULT_USER_AGENT
+    } else if HeaderValue::from_str(&entry.user_agent).is_err() {
+        tracing::warn!("Ignoring invalid user agent for {}", entry.link);
+        DEFAULT_USER_AGENT
+    } else {
+        &entry.user_agent
+    };
+    let builder = Client::builder()
+        .timeout(Duration::from_secs(entry.timeout.unwrap_or(default_timeout)))
+        .connect_timeout(Duration::from_secs(5))
+        .user_agent(user_agent)
+        .redirect(Policy::none());
+    with_proxy(builder, proxy)
+        .build()
+        .expect("Failed to build HTTP client")
+}
+
+/// 友链单独设置的请求头
+/// 不作为客户端的默认请求头，而是随每个请求发送，使重定向到其他站点时可以去掉
+pub fn friend_headers(entry: &FriendEntry) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &entry.headers {
+        match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+            (Ok(name), Ok(value)) => {
+                headers.insert(name, value);
+            }
+            _ => tracing::warn!("Ignoring invalid header {} for {}", name, entry.link),
+        }
+    }
+    headers
+}
+
+/// 按主机规则为每个请求选择是否走代理
+/// 配置了代理时不再读取 HTTP_PROXY 等系统代理环境变量，未配置时保持 reqwest 的默认行为
+fn with_proxy(builder: ClientBuilder, proxy: &ProxyConfig) -> ClientBuilder {
+    // 地址在读取配置时已经校验
+    let proxy_url = match Url::parse(&proxy.url) {
+        Ok(url) => url,
+        Err(_) => return builder,
+    };
+    let config = proxy.clone();
+    builder.proxy(Proxy::custom(move |url| {
+        url.host_str()
+            .filter(|host| config.should_proxy(host))
+            .map(|_| proxy_url.clone())
+    }))
+}
+
 /// 开始爬取友链页面
 pub async fn start_crawl_linkpages(
     fc_settings: &FcSettings,
@@ -24,29 +91,30 @@ pub async fn start_crawl_linkpages(
     
     if fc_settings.enable_link_page {
         for link_page in &fc_settings.link_pages {
-            match crawl_link_page(link_page, &css_rules.link_page_rules, client).await {
+            match crawl_link_page(&link_page.link, &css_rules.link_page_rules, &fc_settings.http, client).await {
                 Ok(friends) => {
-                    // 过滤掉被屏蔽的站点
+                    // 过滤掉被屏蔽的站点，并标记友链页所属分组
                     let filtered_friends: Vec<Friends> = friends
                         .into_iter()
-                        .filter(|friend| {
-                            !fc_settings.block_sites.iter().any(|block| {
-                                friend.link.contains(block)
-                            })
+                        .filter(|friend| !is_blocked_site(&friend.link, &fc_settings.block_sites))
+                        .map(|mut friend| {
+                            friend.link = clean_url(&friend.link);
+                            friend.group = link_page.group.clone();
+                            friend
                         })
                         .collect();
                     all_friends.extend(filtered_friends);
                 }
                 Err(e) => {
-                    eprintln!("Failed to crawl link page {}: {}", link_page, e);
+                    tracing::error!("Failed to crawl link page {} ({}): {}", link_page.link, e.category(), e);
                 }
             }
         }
     }
     
     // 去重
+    let mut all_friends = dedupe_friends(all_friends);
     all_friends.sort_by(|a, b| a.link.cmp(&b.link));
-    all_friends.dedup_by(|a, b| a.link == b.link);
     
     all_friends
 }
@@ -56,31 +124,130 @@ pub async fn start_crawl_postpages(
     link: &str,
     fc_settings: &FcSettings,
     custom_rss: String,
+    max_posts: Option<usize>,
     css_rules: &CssRules,
+    headers: &HeaderMap,
     client: &Client,
-) -> Result<Vec<PostMeta>, Box<dyn std::error::Error>> {
+) -> Result<PostPage> {
     // 检查是否在屏蔽列表中
-    if fc_settings.block_sites.iter().any(|block| link.contains(block)) {
-        return Ok(Vec::new());
+    if is_blocked_site(link, &fc_settings.block_sites) {
+        return Err(Error::Blocked {
+            url: link.to_string(),
+            status: 0,
+            reason: BlockReason::BlockSite,
+            kind: ContentKind::Html,
+        });
     }
     
-    crawl_post_page(link, fc_settings, &custom_rss, &css_rules.post_page_rules, client).await
+    crawl_post_page(link, fc_settings, &custom_rss, max_posts, &css_rules.post_page_rules, headers, client).await
 }
 
-/// 从JSON API或文件获取友链列表
-pub async fn start_get_friends_links_from_json(
-    json_api_or_path: &str,
+/// 从JSON/YAML API或文件获取友链列表
+pub async fn start_get_friends_links_from_source(
+    source: &FriendSource,
+    http: &HttpConfig,
     client: &Client,
-) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
-    if json_api_or_path.starts_with("http") {
+) -> Result<Vec<Friends>> {
+    let content = if source.path.starts_with("http") {
         // 从API获取
-        let response = client.get(json_api_or_path).send().await?;
-        let json: serde_json::Value = response.json().await?;
-        Ok(json)
+        fetch_text(client, &source.path, &HeaderMap::new(), ContentKind::Data, http).await?
     } else {
         // 从文件读取
-        let file_content = std::fs::read_to_string(json_api_or_path)?;
-        let json: serde_json::Value = serde_json::from_str(&file_content)?;
-        Ok(json)
+        std::fs::read_to_string(&source.path).map_err(|e| Error::io(Path::new(&source.path), e))?
+    };
+    
+    let document = parse_document(&content).map_err(|e| Error::parse(&source.path, e))?;
+    Ok(extract_friends(&document, source.format))
+}
+
+/// 检查每个友链的友链页中是否链接回本站
+pub async fn start_check_reciprocal(
+    friends: &[Friends],
+    fc_settings: &FcSettings,
+    css_rules: &CssRules,
+    client: &Client,
+) -> Vec<ReciprocalResult> {
+    let own_hosts: Vec<String> = fc_settings.reciprocal.sites
+        .iter()
+        .filter_map(|site| site_host(site))
+        .collect();
+    // 限制同时检查的友链数，避免同时请求所有友链的友链页
+    let semaphore = Arc::new(Semaphore::new(fc_settings.reciprocal.concurrency.max(1)));
+    let mut tasks = Vec::new();
+    
+    for friend in friends {
+        let candidates = link_page_candidates(&friend.link, fc_settings);
+        let semaphore_clone = semaphore.clone();
+        let link_page_rules = css_rules.link_page_rules.clone();
+        let http_clone = fc_settings.http.clone();
+        let client_clone = client.clone();
+        let own_hosts_clone = own_hosts.clone();
+        let friend_clone = friend.clone();
+        
+        let task = task::spawn(async move {
+            let _permit = semaphore_clone.acquire_owned().await;
+            let mut result = ReciprocalResult {
+                name: friend_clone.name.clone(),
+                link: friend_clone.link.clone(),
+                link_page: String::new(),
+                status: ReciprocalStatus::Unknown,
+                removed: false,
+            };
+            
+            if let Some((page, found)) = find_link_page(&candidates, &link_page_rules, &http_clone, &client_clone).await {
+                let linked = found.iter().any(|f| {
+                    site_host(&f.link).is_some_and(|host| own_hosts_clone.contains(&host))
+                });
+                result.link_page = page;
+                result.status = if linked { ReciprocalStatus::Linked } else { ReciprocalStatus::Missing };
+            }
+            
+            result
+        });
+        
+        tasks.push(task);
+    }
+    
+    let mut results = Vec::new();
+    for task in tasks {
+        match task.await {
+            Ok(result) => results.push(result),
+            Err(e) => tracing::error!("Reciprocal check task failed: {}", e),
+        }
+    }
+    
+    results
+}
+
+/// 友链的友链页候选地址：优先使用配置的地址，否则尝试常见路径
+pub fn link_page_candidates(link: &str, fc_settings: &FcSettings) -> Vec<String> {
+    match fc_settings.reciprocal.link_pages.get(link) {
+        Some(page) => vec![page.clone()],
+        None => fc_settings.reciprocal.paths
+            .iter()
+            .filter_map(|path| Url::parse(link).ok()?.join(path).ok())
+            .map(|url| url.to_string())
+            .collect(),
     }
+}
+
+/// 依次尝试候选地址，返回第一个能提取出友链的页面及其友链
+pub async fn find_link_page(
+    candidates: &[String],
+    link_page_rules: &serde_yaml::Value,
+    http: &HttpConfig,
+    client: &Client,
+) -> Option<(String, Vec<Friends>)> {
+    for page in candidates {
+        match crawl_link_page(page, link_page_rules, http, client).await {
+            Ok(found) if !found.is_empty() => return Some((page.clone(), found)),
+            _ => continue,
+        }
+    }
+    None
+}
+
+/// 判断链接是否在屏蔽列表中
+pub fn is_blocked_site(link: &str, block_sites: &[String]) -> bool {
+    block_sites.iter().any(|block| link.contains(block))
 }
\ No newline at end of file
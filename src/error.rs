@@ -0,0 +1,135 @@
+use std::fmt;
+use std::path::Path;
+use crate::fetch::ContentKind;
+
+/// 程序中的错误，均携带出错的地址或路径，可以直接跨任务传递
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// 连接失败、DNS解析失败等网络错误
+    Network { url: String, message: String },
+    /// 请求超时
+    Timeout { url: String },
+    /// 非2xx状态码
+    HttpStatus { url: String, status: u16, kind: ContentKind },
+    /// 被屏蔽：反爬验证页、域名停放页或 BLOCK_SITE
+    Blocked { url: String, status: u16, reason: BlockReason, kind: ContentKind },
+    /// 响应正文超过大小上限
+    TooLarge { url: String, limit: usize },
+    /// 重定向次数过多，通常是重定向循环
+    TooManyRedirects { url: String, limit: usize },
+    /// 响应类型与期望不符，如订阅源地址返回HTML错误页、主页地址返回PDF
+    ContentType { url: String, content_type: String, expected: ContentKind },
+    /// 内容无法解析
+    Parse { url: String, message: String },
+    /// 配置文件无法读取或解析
+    Config { path: String, message: String },
+    /// 没有找到文章
+    NoPosts { url: String },
+    /// 文件读写失败
+    Io { path: String, message: String },
+}
+
+/// 被屏蔽的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockReason {
+    /// Cloudflare 等反爬验证页
+    Challenge,
+    /// 域名已停放或出售
+    Parked,
+    /// 在 BLOCK_SITE 中
+    BlockSite,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// 将请求错误按是否超时分类
+    pub fn request(url: &str, error: reqwest::Error) -> Error {
+        if error.is_timeout() {
+            Error::Timeout { url: url.to_string() }
+        } else {
+            Error::Network { url: url.to_string(), message: error.to_string() }
+        }
+    }
+    
+    pub fn parse(url: &str, error: impl fmt::Display) -> Error {
+        Error::Parse { url: url.to_string(), message: error.to_string() }
+    }
+    
+    pub fn config(path: &str, error: impl fmt::Display) -> Error {
+        Error::Config { path: path.to_string(), message: error.to_string() }
+    }
+    
+    pub fn io(path: &Path, error: impl fmt::Display) -> Error {
+        Error::Io { path: path.display().to_string(), message: error.to_string() }
+    }
+    
+    /// 错误分类，用于抓取记录、friends.json 与统计信息
+    pub fn category(&self) -> &'static str {
+        match self {
+            Error::Network { .. } => "network",
+            Error::Timeout { .. } => "timeout",
+            Error::HttpStatus { .. } => "http_status",
+            Error::Blocked { .. } => "blocked",
+            Error::TooLarge { .. } => "too_large",
+            Error::TooManyRedirects { .. } => "redirect",
+            Error::ContentType { .. } => "content_type",
+            Error::Parse { .. } => "parse",
+            Error::Config { .. } => "config",
+            Error::NoPosts { .. } => "no_posts",
+            Error::Io { .. } => "io",
+        }
+    }
+    
+    /// 响应状态码，没有收到响应或与状态码无关时为0
+    pub fn status(&self) -> u16 {
+        match self {
+            Error::HttpStatus { status, .. } | Error::Blocked { status, .. } => *status,
+            _ => 0,
+        }
+    }
+    
+    /// 出错时程序的退出码
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Config { .. } => 2,
+            Error::Io { .. } => 3,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Network { url, message } => write!(f, "network error on {}: {}", url, message),
+            Error::Timeout { url } => write!(f, "request to {} timed out", url),
+            Error::HttpStatus { url, status, kind } => write!(f, "HTTP {} on {} {}", status, kind, url),
+            Error::Blocked { url, status: 0, reason, kind } => write!(f, "{} on {} {}", reason, kind, url),
+            Error::Blocked { url, status, reason, kind } => {
+                write!(f, "{} (HTTP {}) on {} {}", reason, status, kind, url)
+            }
+            Error::TooLarge { url, limit } => write!(f, "response from {} exceeds {} bytes", url, limit),
+            Error::TooManyRedirects { url, limit } => write!(f, "more than {} redirects from {}", limit, url),
+            Error::ContentType { url, content_type, expected } => {
+                write!(f, "unexpected content type {} from {}, expected {}", content_type, url, expected)
+            }
+            Error::Parse { url, message } => write!(f, "failed to parse {}: {}", url, message),
+            Error::Config { path, message } => write!(f, "invalid configuration {}: {}", path, message),
+            Error::NoPosts { url } => write!(f, "no posts found on {}", url),
+            Error::Io { path, message } => write!(f, "I/O error on {}: {}", path, message),
+        }
+    }
+}
+
+impl fmt::Display for BlockReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockReason::Challenge => write!(f, "challenge page"),
+            BlockReason::Parked => write!(f, "parked domain"),
+            BlockReason::BlockSite => write!(f, "listed in BLOCK_SITE"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
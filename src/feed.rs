@@ -0,0 +1,352 @@
+use chrono::{FixedOffset, Utc};
+use roxmltree::{Document, Node, ParsingOptions};
+use serde_json::Value;
+use crate::crawler::{decode_html_entities, parse_rss_time, resolve_relative_url};
+use crate::error::{Error, Result};
+use crate::models::{Enclosure, PostMeta};
+
+const BEIJING_OFFSET: Option<FixedOffset> = FixedOffset::east_opt(8 * 3600);
+
+/// Media RSS 命名空间
+const MEDIA_NS: &str = "http://search.yahoo.com/mrss/";
+
+/// xml:lang 所在的命名空间
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// 解析订阅源，支持 RSS 2.0、RSS 1.0 (RDF)、Atom 与 JSON Feed
+pub fn parse_feed(text: &str, feed_url: &str) -> Result<Vec<PostMeta>> {
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    if text.starts_with('{') {
+        let document: Value = serde_json::from_str(text).map_err(|e| Error::parse(feed_url, e))?;
+        return Ok(parse_json_feed(&document, feed_url));
+    }
+    
+    let options = ParsingOptions { allow_dtd: true, ..ParsingOptions::default() };
+    let doc = Document::parse_with_options(text, options).map_err(|e| Error::parse(feed_url, e))?;
+    let root = doc.root_element();
+    
+    let posts = match root.tag_name().name() {
+        "feed" => root.children()
+            .filter(|node| node.tag_name().name() == "entry")
+            .filter_map(|entry| parse_atom_entry(entry, text, feed_url))
+            .collect(),
+        // RSS 2.0 的条目位于 channel 下，RSS 1.0 的条目与 channel 同级
+        _ => {
+            let channel_lang = root.descendants()
+                .find(|node| node.tag_name().name() == "channel")
+                .and_then(|channel| child_text(channel, "language"))
+                .unwrap_or_default();
+            root.descendants()
+                .filter(|node| node.tag_name().name() == "item")
+                .filter_map(|item| parse_rss_item(item, feed_url, &channel_lang))
+                .collect()
+        }
+    };
+    
+    Ok(posts)
+}
+
+/// 解析 RSS 条目
+fn parse_rss_item(item: Node, feed_url: &str, channel_lang: &str) -> Option<PostMeta> {
+    let link = child_text(item, "link")
+        .or_else(|| child(item, "guid")
+            .filter(|guid| guid.attribute("isPermaLink") != Some("false"))
+            .map(node_text)
+            .filter(|guid| guid.starts_with("http")))?;
+    let link = resolve_relative_url(&link, feed_url).ok()?;
+    
+    let created = ["pubDate", "date", "published"]
+        .iter()
+        .find_map(|name| child_text(item, name).map(|t| parse_rss_time(&t)).filter(|t| !t.is_empty()))
+        .unwrap_or_else(now_string);
+    let updated = child_text(item, "updated")
+        .map(|t| parse_rss_time(&t))
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| created.clone());
+    
+    // content:encoded 优先于 description
+    let content = child_text(item, "encoded")
+        .or_else(|| child_text(item, "description"))
+        .unwrap_or_default();
+    
+    // RSS 1.0 没有 guid，以 rdf:about 作为标识
+    let guid = child_text(item, "guid")
+        .or_else(|| item.attributes().find(|a| a.name() == "about").map(|a| a.value().trim().to_string()))
+        .unwrap_or_default();
+    
+    let tags = unique(item.children()
+        .filter(|node| node.tag_name().name() == "category")
+        .map(node_text)
+        .collect());
+    
+    let enclosures: Vec<Enclosure> = item.children()
+        .filter(|node| node.tag_name().name() == "enclosure")
+        .filter_map(|node| make_enclosure(node.attribute("url"), node.attribute("type"), node.attribute("length"), feed_url))
+        .collect();
+    
+    Some(PostMeta {
+        title: child_text(item, "title").map(|t| decode_html_entities(&t)).unwrap_or_default(),
+        link,
+        created,
+        updated,
+        content,
+        summary: String::new(),
+        guid,
+        tags,
+        cover: find_cover(item, &enclosures, feed_url),
+        enclosures,
+        // 条目的 dc:language 优先于频道的 language
+        lang: child_text(item, "language")
+            .or_else(|| xml_lang(item))
+            .unwrap_or_else(|| channel_lang.to_string()),
+        word_count: 0,
+        reading_minutes: 0,
+    })
+}
+
+/// 解析 Atom 条目
+fn parse_atom_entry(entry: Node, source: &str, feed_url: &str) -> Option<PostMeta> {
+    // 优先使用 rel="alternate" 或未指定 rel 的链接
+    let link = entry.children()
+        .filter(|node| node.tag_name().name() == "link")
+        .find(|node| matches!(node.attribute("rel"), None | Some("alternate")))
+        .and_then(|node| node.attribute("href"))
+        .map(|href| href.trim().to_string())?;
+    let link = resolve_relative_url(&link, feed_url).ok()?;
+    
+    let published = child_text(entry, "published").map(|t| parse_rss_time(&t)).filter(|t| !t.is_empty());
+    let updated = child_text(entry, "updated").map(|t| parse_rss_time(&t)).filter(|t| !t.is_empty());
+    let created = published.clone().or_else(|| updated.clone()).unwrap_or_else(now_string);
+    let updated = updated.unwrap_or_else(|| created.clone());
+    
+    let content = child(entry, "content")
+        .or_else(|| child(entry, "summary"))
+        .map(|node| atom_text(node, source))
+        .unwrap_or_default();
+    
+    // Atom 分类的名称在 term 属性中，label 为可选的展示名
+    let tags = unique(entry.children()
+        .filter(|node| node.tag_name().name() == "category")
+        .filter_map(|node| node.attribute("label").or_else(|| node.attribute("term")))
+        .map(|tag| tag.trim().to_string())
+        .collect());
+    
+    let enclosures: Vec<Enclosure> = entry.children()
+        .filter(|node| node.tag_name().name() == "link" && node.attribute("rel") == Some("enclosure"))
+        .filter_map(|node| make_enclosure(node.attribute("href"), node.attribute("type"), node.attribute("length"), feed_url))
+        .collect();
+    
+    Some(PostMeta {
+        title: child(entry, "title").map(|node| decode_html_entities(&atom_text(node, source))).unwrap_or_default(),
+        link,
+        created,
+        updated,
+        content,
+        summary: String::new(),
+        guid: child_text(entry, "id").unwrap_or_default(),
+        tags,
+        cover: find_cover(entry, &enclosures, feed_url),
+        enclosures,
+        lang: child(entry, "content")
+            .and_then(|content| content.attribute((XML_NS, "lang")))
+            .map(|lang| lang.to_string())
+            .or_else(|| xml_lang(entry))
+            .unwrap_or_default(),
+        word_count: 0,
+        reading_minutes: 0,
+    })
+}
+
+/// 解析 JSON Feed 条目
+fn parse_json_feed(document: &Value, feed_url: &str) -> Vec<PostMeta> {
+    let items = match document.get("items").and_then(|items| items.as_array()) {
+        Some(items) => items,
+        None => return Vec::new(),
+    };
+    
+    let feed_lang = document.get("language").and_then(|l| l.as_str()).unwrap_or("");
+    
+    items.iter()
+        .filter_map(|item| {
+            let field = |key: &str| item.get(key).and_then(|v| v.as_str()).map(|s| s.trim().to_string());
+            
+            let link = field("url").or_else(|| field("external_url"))?;
+            let link = resolve_relative_url(&link, feed_url).ok()?;
+            let created = field("date_published")
+                .map(|t| parse_rss_time(&t))
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(now_string);
+            let updated = field("date_modified")
+                .map(|t| parse_rss_time(&t))
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(|| created.clone());
+            // id 可能是数字
+            let guid = match item.get("id") {
+                Some(Value::String(id)) => id.trim().to_string(),
+                Some(Value::Number(id)) => id.to_string(),
+                _ => String::new(),
+            };
+            let tags = item.get("tags")
+                .and_then(|tags| tags.as_array())
+                .map(|tags| unique(tags.iter().filter_map(|t| t.as_str()).map(|t| t.trim().to_string()).collect()))
+                .unwrap_or_default();
+            let enclosures = item.get("attachments")
+                .and_then(|attachments| attachments.as_array())
+                .map(|attachments| attachments.iter()
+                    .filter_map(|a| {
+                        let size = a.get("size_in_bytes").and_then(|s| s.as_u64()).map(|s| s.to_string());
+                        make_enclosure(
+                            a.get("url").and_then(|u| u.as_str()),
+                            a.get("mime_type").and_then(|m| m.as_str()),
+                            size.as_deref(),
+                            feed_url,
+                        )
+                    })
+                    .collect())
+                .unwrap_or_default();
+            let cover = field("image")
+                .or_else(|| field("banner_image"))
+                .and_then(|image| resolve_relative_url(&image, feed_url).ok())
+                .unwrap_or_default();
+            
+            Some(PostMeta {
+                title: field("title").map(|t| decode_html_entities(&t)).unwrap_or_default(),
+                link,
+                created,
+                updated,
+                content: field("content_html").or_else(|| field("content_text")).unwrap_or_default(),
+                summary: field("summary").map(|t| decode_html_entities(&t)).unwrap_or_default(),
+                guid,
+                tags,
+                cover,
+                enclosures,
+                lang: field("language").unwrap_or_else(|| feed_lang.to_string()),
+                word_count: 0,
+                reading_minutes: 0,
+            })
+        })
+        .collect()
+}
+
+/// 查找封面图：依次尝试 media:thumbnail、图片类型的 media:content、itunes:image 与图片附件
+fn find_cover(node: Node, enclosures: &[Enclosure], feed_url: &str) -> String {
+    // media 元素可能包在 media:group 中
+    let media: Vec<Node> = node.descendants()
+        .filter(|n| n.tag_name().namespace() == Some(MEDIA_NS))
+        .collect();
+    
+    let thumbnail = media.iter()
+        .find(|n| n.tag_name().name() == "thumbnail")
+        .and_then(|n| n.attribute("url"));
+    let media_image = media.iter()
+        .filter(|n| n.tag_name().name() == "content")
+        .find(|n| {
+            n.attribute("medium") == Some("image")
+                || n.attribute("type").is_some_and(|t| t.starts_with("image/"))
+        })
+        .and_then(|n| n.attribute("url"));
+    let itunes_image = node.children()
+        .find(|n| n.tag_name().name() == "image")
+        .and_then(|n| n.attribute("href"));
+    let enclosure_image = enclosures.iter()
+        .find(|e| e.mime_type.starts_with("image/"))
+        .map(|e| e.url.as_str());
+    
+    thumbnail
+        .or(media_image)
+        .or(itunes_image)
+        .or(enclosure_image)
+        .and_then(|url| resolve_relative_url(url.trim(), feed_url).ok())
+        .unwrap_or_default()
+}
+
+/// 元素自身或祖先元素上的 xml:lang
+fn xml_lang(node: Node) -> Option<String> {
+    node.ancestors()
+        .find_map(|n| n.attribute((XML_NS, "lang")))
+        .map(|lang| lang.trim().to_string())
+        .filter(|lang| !lang.is_empty())
+}
+
+/// 构造附件，缺少地址时忽略
+fn make_enclosure(url: Option<&str>, mime_type: Option<&str>, length: Option<&str>, feed_url: &str) -> Option<Enclosure> {
+    let url = url.map(|u| u.trim()).filter(|u| !u.is_empty())?;
+    Some(Enclosure {
+        url: resolve_relative_url(url, feed_url).ok()?,
+        mime_type: mime_type.unwrap_or_default().trim().to_string(),
+        length: length.and_then(|l| l.trim().parse().ok()).unwrap_or(0),
+    })
+}
+
+/// 去掉空值与重复项，保持原有顺序
+fn unique(values: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for value in values {
+        if !value.is_empty() && !result.contains(&value) {
+            result.push(value);
+        }
+    }
+    result
+}
+
+/// 查找指定本地名的子元素，忽略命名空间前缀
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.tag_name().name() == name)
+}
+
+/// 子元素的文本，文本为空时视为不存在
+fn child_text(node: Node, name: &str) -> Option<String> {
+    node.children()
+        .filter(|child| child.tag_name().name() == name)
+        .map(node_text)
+        .find(|text| !text.is_empty())
+}
+
+/// 元素内全部文本（含 CDATA）
+fn node_text(node: Node) -> String {
+    node.descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Atom 文本结构：type="xhtml" 时保留内部标签，其余取文本
+fn atom_text(node: Node, source: &str) -> String {
+    if node.attribute("type") != Some("xhtml") {
+        return node_text(node);
+    }
+    
+    let start = node.first_child().map(|n| n.range().start);
+    let end = node.last_child().map(|n| n.range().end);
+    match (start, end) {
+        (Some(start), Some(end)) if start < end => source[start..end].trim().to_string(),
+        _ => String::new(),
+    }
+}
+
+/// 无法解析时间时使用当前时间
+fn now_string() -> String {
+    Utc::now().with_timezone(&BEIJING_OFFSET.unwrap()).format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn decodes_rss_title_once() {
+        let xml = r#"<rss><channel><item><title>2023 &amp;#8211; 2024</title><link>https://example.com/a</link></item></channel></rss>"#;
+        let posts = parse_feed(xml, "https://example.com/feed").unwrap();
+        assert_eq!(posts[0].title, "2023 – 2024");
+    }
+    
+    #[test]
+    fn decodes_json_title_and_summary() {
+        let json = r#"{"items":[{"url":"https://example.com/a","title":"Tom &amp; Jerry","summary":"&lt;3"}]}"#;
+        let posts = parse_feed(json, "https://example.com/feed.json").unwrap();
+        assert_eq!(posts[0].title, "Tom & Jerry");
+        assert_eq!(posts[0].summary, "<3");
+    }
+}
@@ -0,0 +1,362 @@
+use encoding_rs::{Encoding, GB18030, UTF_16BE, UTF_16LE, UTF_8};
+use reqwest::header::{HeaderMap, CONTENT_TYPE, LOCATION};
+use reqwest::{Client, Response, StatusCode};
+use url::Url;
+use crate::config::HttpConfig;
+use crate::crawler::site_host;
+use crate::error::{BlockReason, Error, Result};
+use crate::models::Redirect;
+
+/// 最多跟随的重定向次数
+const MAX_REDIRECTS: usize = 10;
+
+/// 查找文档内字符集声明的范围（字节数）
+const SNIFF_LENGTH: usize = 4096;
+
+/// 查找验证页、停放页特征的范围（字符数）
+const MARKER_LENGTH: usize = 8192;
+
+/// 反爬验证页的标题
+const CHALLENGE_TITLES: [&str; 4] = [
+    "just a moment...",
+    "attention required! | cloudflare",
+    "ddos-guard",
+    "sucuri website firewall",
+];
+
+/// 只出现在 Cloudflare 验证页中的标记
+const CHALLENGE_SCRIPTS: [&str; 2] = [
+    "cf_chl_opt",
+    "cf-browser-verification",
+];
+
+/// 反爬验证页的其他特征，正常页面中也可能出现，只用于检查非2xx响应
+const CHALLENGE_MARKERS: [&str; 3] = [
+    "/cdn-cgi/challenge-platform/",
+    "ddos-guard",
+    "sucuri website firewall",
+];
+
+/// 域名停放页的特征，2xx响应只在标题中查找
+const PARKING_MARKERS: [&str; 9] = [
+    "sedoparking",
+    "parkingcrew",
+    "bodis.com",
+    "this domain is for sale",
+    "this domain may be for sale",
+    "buy this domain",
+    "parked free, courtesy of godaddy",
+    "该域名正在出售",
+    "此域名正在出售",
+];
+
+/// 期望的响应内容，用于检查响应类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKind {
+    /// 友链页、主页与文章页
+    Html,
+    /// RSS、Atom 或 JSON Feed 订阅源
+    Feed,
+    /// JSON/YAML 友链列表
+    Data,
+}
+
+impl std::fmt::Display for ContentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentKind::Html => write!(f, "page"),
+            ContentKind::Feed => write!(f, "feed"),
+            ContentKind::Data => write!(f, "friend list"),
+        }
+    }
+}
+
+/// 请求地址并将响应正文按检测到的字符集转换为UTF-8
+/// 正文分块读取，超过 max_body_size 时立即中止；非2xx状态、验证页与停放页作为错误返回；
+/// 开启类型检查时拒绝与期望不符的响应；headers 为友链单独设置的请求头，只发送给友链自己的站点
+pub async fn fetch_text(client: &Client, url: &str, headers: &HeaderMap, kind: ContentKind, config: &HttpConfig) -> Result<String> {
+    fetch_document(client, url, headers, kind, config).await.map(|(text, _)| text)
+}
+
+/// 与 fetch_text 相同，同时返回重定向后的地址
+pub async fn fetch_document(client: &Client, url: &str, headers: &HeaderMap, kind: ContentKind, config: &HttpConfig) -> Result<(String, Redirect)> {
+    let (mut response, redirect) = follow_redirects(client, url, headers).await?;
+    // 之后的错误均针对最终地址
+    let url = redirect.final_url.as_str();
+    let status = response.status().as_u16();
+    let content_type = response.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    let blocked = |reason| Error::Blocked { url: url.to_string(), status, reason, kind };
+    
+    // Cloudflare 在验证页的响应头中标明
+    let cf_challenge = response.headers()
+        .get("cf-mitigated")
+        .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"challenge"));
+    if cf_challenge {
+        return Err(blocked(BlockReason::Challenge));
+    }
+    if !response.status().is_success() {
+        // 错误页无需完整读取，只查看开头判断是否为验证页
+        let head = response.chunk().await.ok().flatten().unwrap_or_default();
+        return Err(match blocked_reason(&String::from_utf8_lossy(&head), true) {
+            Some(reason) => blocked(reason),
+            None => Error::HttpStatus { url: url.to_string(), status, kind },
+        });
+    }
+    
+    let content_type_error = |content_type: String| Error::ContentType { url: url.to_string(), content_type, expected: kind };
+    if config.check_content_type && is_binary_type(&content_type) {
+        return Err(content_type_error(content_type));
+    }
+    
+    let limit = config.max_body_size;
+    let too_large = || Error::TooLarge { url: url.to_string(), limit };
+    if limit > 0 && response.content_length().is_some_and(|length| length as usize > limit) {
+        return Err(too_large());
+    }
+    
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| Error::request(url, e))? {
+        if limit > 0 && bytes.len() + chunk.len() > limit {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    
+    let text = decode_body(&bytes, &content_type);
+    if kind != ContentKind::Data && looks_like_html(&text) {
+        if let Some(reason) = blocked_reason(&text, false) {
+            return Err(blocked(reason));
+        }
+        if config.check_content_type && kind == ContentKind::Feed {
+            return Err(content_type_error(content_type));
+        }
+    }
+    
+    Ok((text, redirect))
+}
+
+/// 只检查地址的重定向，不读取正文
+pub async fn resolve_redirect(client: &Client, url: &str, headers: &HeaderMap) -> Result<Redirect> {
+    follow_redirects(client, url, headers).await.map(|(_, redirect)| redirect)
+}
+
+/// 手动跟随重定向，记录最终地址以及只经过永久重定向到达的地址
+/// 客户端关闭了自动重定向，否则无法得知中间每一跳的状态码
+/// 也因此需要自行处理请求头：重定向到其他站点后不再携带 headers，以免泄露 Cookie、Authorization 等
+async fn follow_redirects(client: &Client, url: &str, headers: &HeaderMap) -> Result<(Response, Redirect)> {
+    let host = site_host(url);
+    let mut current = url.to_string();
+    // 从起点开始连续经过 301、308 到达的地址，中间出现临时重定向后不再更新
+    let mut permanent_url = url.to_string();
+    let mut permanent = true;
+    
+    for _ in 0..=MAX_REDIRECTS {
+        let mut request = client.get(&current);
+        if site_host(&current) == host {
+            request = request.headers(headers.clone());
+        }
+        let response = request.send().await.map_err(|e| Error::request(&current, e))?;
+        let next = if response.status().is_redirection() {
+            response.headers()
+                .get(LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|location| Url::parse(&current).ok()?.join(location.trim()).ok())
+        } else {
+            None
+        };
+        
+        // 不是重定向，或是没有 Location 的 304 等响应，交给调用方按状态码处理
+        let next = match next {
+            Some(next) => next.to_string(),
+            None => {
+                let moved_to = if site_host(&permanent_url) != host { permanent_url } else { String::new() };
+                return Ok((response, Redirect { final_url: current, moved_to }));
+            }
+        };
+        
+        permanent = permanent && matches!(response.status(), StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT);
+        if permanent {
+            permanent_url = next.clone();
+        }
+        tracing::debug!("{} redirected to {} ({})", current, next, response.status());
+        current = next;
+    }
+    
+    Err(Error::TooManyRedirects { url: url.to_string(), limit: MAX_REDIRECTS })
+}
+
+/// 判断HTML是否为反爬验证页或域名停放页，只查看开头部分
+/// 正常页面的正文可能提到这些特征，因此 full_scan 为 false 时只检查标题与验证页专有的标记
+fn blocked_reason(html: &str, full_scan: bool) -> Option<BlockReason> {
+    let head: String = html.chars().take(MARKER_LENGTH).collect::<String>().to_lowercase();
+    let title = page_title(&head);
+    let in_title = |markers: &[&str]| markers.iter().any(|marker| title.contains(marker));
+    let in_head = |markers: &[&str]| markers.iter().any(|marker| head.contains(marker));
+    
+    if in_title(&CHALLENGE_TITLES) || in_head(&CHALLENGE_SCRIPTS) || (full_scan && in_head(&CHALLENGE_MARKERS)) {
+        Some(BlockReason::Challenge)
+    } else if in_title(&PARKING_MARKERS) || (full_scan && in_head(&PARKING_MARKERS)) {
+        Some(BlockReason::Parked)
+    } else {
+        None
+    }
+}
+
+/// 页面 <title> 标签中的文字，没有时返回空字符串
+fn page_title(html: &str) -> &str {
+    let Some(start) = html.find("<title") else {
+        return "";
+    };
+    let Some(open_end) = html[start..].find('>').map(|i| start + i + 1) else {
+        return "";
+    };
+    let end = html[open_end..].find("</title>").map_or(html.len(), |i| open_end + i);
+    html[open_end..end].trim()
+}
+
+/// 不可能是文本内容的响应类型
+fn is_binary_type(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or("").trim().to_lowercase();
+    mime.starts_with("image/")
+        || mime.starts_with("audio/")
+        || mime.starts_with("video/")
+        || mime.starts_with("font/")
+        || matches!(
+            mime.as_str(),
+            "application/pdf" | "application/octet-stream" | "application/zip" | "application/gzip" | "application/x-gzip"
+        )
+}
+
+/// 内容是否为HTML页面；部分服务器以 text/html 返回订阅源，因此按正文而不是响应头判断
+fn looks_like_html(text: &str) -> bool {
+    let head: String = text.trim_start_matches('\u{feff}').trim_start().chars().take(256).collect::<String>().to_lowercase();
+    head.starts_with("<!doctype html") || head.starts_with("<html") || head.starts_with("<head") || head.starts_with("<body")
+}
+
+/// 按 BOM、XML 声明、meta 标签、响应头的顺序确定字符集并解码
+/// 都没有声明且内容不是合法的UTF-8时按 GB18030（兼容 GBK、GB2312）解码
+pub fn decode_body(bytes: &[u8], content_type: &str) -> String {
+    let encoding = Encoding::for_bom(bytes)
+        .map(|(encoding, _)| encoding)
+        .or_else(|| sniff_document_charset(bytes))
+        .or_else(|| charset_param(content_type).and_then(|label| Encoding::for_label(label.as_bytes())));
+    
+    let encoding = match encoding {
+        Some(encoding) => encoding,
+        None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+        None => GB18030,
+    };
+    
+    // decode 会识别并去掉 BOM
+    let (text, used, had_errors) = encoding.decode(bytes);
+    if had_errors {
+        tracing::debug!("Invalid {} sequences replaced while decoding", used.name());
+    }
+    text.into_owned()
+}
+
+/// 从 XML 声明或 HTML meta 标签中读取字符集
+fn sniff_document_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    let head = &bytes[..bytes.len().min(SNIFF_LENGTH)];
+    // 声明本身只含ASCII字符，其余字节按有损方式转换不影响查找
+    let head = String::from_utf8_lossy(head).to_lowercase();
+    
+    let label = if head.trim_start().starts_with("<?xml") {
+        let decl_end = head.find("?>").unwrap_or(head.len());
+        attribute_value(&head[..decl_end], "encoding")
+    } else {
+        None
+    };
+    
+    let label = label.or_else(|| {
+        head.match_indices("<meta")
+            .filter_map(|(start, _)| {
+                let end = head[start..].find('>').map_or(head.len(), |i| start + i);
+                attribute_value(&head[start..end], "charset")
+            })
+            .next()
+    })?;
+    
+    // 以ASCII方式读出的文档不可能是UTF-16，按HTML标准视为UTF-8
+    let encoding = Encoding::for_label(label.as_bytes())?;
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        Some(UTF_8)
+    } else {
+        Some(encoding)
+    }
+}
+
+/// Content-Type 中的 charset 参数
+fn charset_param(content_type: &str) -> Option<String> {
+    attribute_value(&content_type.to_lowercase(), "charset")
+}
+
+/// 读取 name=value、name="value" 形式的值，兼容 <meta content="text/html; charset=gbk">
+fn attribute_value(text: &str, name: &str) -> Option<String> {
+    let start = text.find(name)? + name.len();
+    let rest = text[start..].trim_start().strip_prefix('=')?.trim_start();
+    let rest = rest.trim_start_matches(['"', '\'']);
+    let value: String = rest
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+        .collect();
+    
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{GBK, SHIFT_JIS};
+    
+    #[test]
+    fn decodes_gbk_from_meta_charset() {
+        let html = "<html><head><meta charset=\"gbk\"><title>中文博客</title></head></html>";
+        let (bytes, _, _) = GBK.encode(html);
+        assert_eq!(decode_body(&bytes, ""), html);
+        
+        // 非GB系编码时必须依赖 meta 声明，不能落到 GB18030 兜底
+        let html = "<meta http-equiv=\"Content-Type\" content=\"text/html; charset=Shift_JIS\"><p>日本語のブログ</p>";
+        let (bytes, _, _) = SHIFT_JIS.encode(html);
+        assert_eq!(decode_body(&bytes, ""), html);
+    }
+    
+    #[test]
+    fn strips_byte_order_mark() {
+        let mut bytes = b"\xEF\xBB\xBF".to_vec();
+        bytes.extend_from_slice("<rss>标题</rss>".as_bytes());
+        assert_eq!(decode_body(&bytes, "text/xml; charset=gbk"), "<rss>标题</rss>");
+        
+        let utf16: Vec<u8> = [0xFF, 0xFE].into_iter()
+            .chain("<rss/>".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        assert_eq!(decode_body(&utf16, ""), "<rss/>");
+    }
+    
+    #[test]
+    fn detects_challenge_and_parking_pages() {
+        let challenge = "<html><head><title>Just a moment...</title></head><body></body></html>";
+        assert_eq!(blocked_reason(challenge, false), Some(BlockReason::Challenge));
+        let script = "<html><body><script>window._cf_chl_opt={cvId:'3'};</script></body></html>";
+        assert_eq!(blocked_reason(script, false), Some(BlockReason::Challenge));
+        let parked = "<html><head><title>example.com - This domain is for sale</title></head></html>";
+        assert_eq!(blocked_reason(parked, false), Some(BlockReason::Parked));
+    }
+    
+    #[test]
+    fn ignores_markers_in_normal_page_body() {
+        let post = "<html><head><title>站点迁移记录</title></head><body>\
+            <p>换用 DDoS-Guard 之后，旧域名挂上了 this domain is for sale 的停放页。</p>\
+            <script src=\"/cdn-cgi/challenge-platform/scripts/jsd/main.js\"></script></body></html>";
+        assert_eq!(blocked_reason(post, false), None);
+        assert_eq!(blocked_reason(post, true), Some(BlockReason::Challenge));
+    }
+}
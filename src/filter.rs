@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use regex::Regex;
+use crate::canonical::canonical_key;
+use crate::config::FilterConfig;
+use crate::content::html_to_text;
+use crate::models::PostMeta;
+
+/// 编译后的文章过滤规则
+pub struct PostFilter {
+    include_title: Vec<Regex>,
+    exclude_title: Vec<Regex>,
+    include_categories: Vec<String>,
+    exclude_categories: Vec<String>,
+    min_content_length: usize,
+    // 键为友链的规范化链接
+    friends: HashMap<String, (Vec<Regex>, Vec<String>)>,
+}
+
+impl PostFilter {
+    /// 从配置编译过滤规则，无效的正则表达式会被忽略
+    pub fn new(config: &FilterConfig) -> PostFilter {
+        PostFilter {
+            include_title: compile_patterns(&config.include_title),
+            exclude_title: compile_patterns(&config.exclude_title),
+            include_categories: lowercase_all(&config.include_categories),
+            exclude_categories: lowercase_all(&config.exclude_categories),
+            min_content_length: config.min_content_length,
+            friends: config.friends
+                .iter()
+                .map(|(link, rules)| {
+                    (canonical_key(link), (compile_patterns(&rules.exclude_title), lowercase_all(&rules.exclude_categories)))
+                })
+                .collect(),
+        }
+    }
+    
+    /// 判断文章是否保留，不保留时返回原因
+    pub fn check(&self, post: &PostMeta, friend_link: &str) -> Result<(), String> {
+        let tags = lowercase_all(&post.tags);
+        
+        if !self.include_title.is_empty() && !self.include_title.iter().any(|re| re.is_match(&post.title)) {
+            return Err("title not included".to_string());
+        }
+        if let Some(re) = self.exclude_title.iter().find(|re| re.is_match(&post.title)) {
+            return Err(format!("title matches {}", re.as_str()));
+        }
+        if !self.include_categories.is_empty() && !tags.iter().any(|tag| self.include_categories.contains(tag)) {
+            return Err("category not included".to_string());
+        }
+        if let Some(tag) = tags.iter().find(|tag| self.exclude_categories.contains(tag)) {
+            return Err(format!("category {}", tag));
+        }
+        
+        if let Some((exclude_title, exclude_categories)) = self.friends.get(&canonical_key(friend_link)) {
+            if let Some(re) = exclude_title.iter().find(|re| re.is_match(&post.title)) {
+                return Err(format!("title matches {}", re.as_str()));
+            }
+            if let Some(tag) = tags.iter().find(|tag| exclude_categories.contains(tag)) {
+                return Err(format!("category {}", tag));
+            }
+        }
+        
+        // 没有正文时（如按CSS规则抓取且未开启全文抓取）无从判断长度，不按长度过滤
+        if self.min_content_length > 0 {
+            let length = html_to_text(&post.content).chars().count();
+            if length > 0 && length < self.min_content_length {
+                return Err(format!("content length {}", length));
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+/// 编译正则表达式列表
+fn compile_patterns(patterns: &[String]) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(re) => Some(re),
+            Err(e) => {
+                tracing::warn!("Ignoring invalid filter pattern {}: {}", pattern, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// 分类比较时忽略大小写与首尾空白
+fn lowercase_all(values: &[String]) -> Vec<String> {
+    values.iter().map(|v| v.trim().to_lowercase()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FriendFilterConfig;
+    
+    fn config() -> FilterConfig {
+        FilterConfig {
+            include_title: Vec::new(),
+            exclude_title: Vec::new(),
+            include_categories: Vec::new(),
+            exclude_categories: Vec::new(),
+            min_content_length: 0,
+            friends: HashMap::new(),
+        }
+    }
+    
+    fn post(title: &str, tags: &[&str]) -> PostMeta {
+        PostMeta {
+            title: title.to_string(),
+            link: String::from("https://example.com/post/1"),
+            created: String::new(),
+            updated: String::new(),
+            content: String::new(),
+            summary: String::new(),
+            guid: String::new(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            cover: String::new(),
+            enclosures: Vec::new(),
+            lang: String::new(),
+            word_count: 0,
+            reading_minutes: 0,
+        }
+    }
+    
+    #[test]
+    fn includes_and_excludes_by_title() {
+        let filter = PostFilter::new(&FilterConfig {
+            include_title: vec![String::from("(?i)rust|折腾")],
+            exclude_title: vec![String::from("周报$"), String::from("[invalid")],
+            ..config()
+        });
+        let link = "https://example.com/";
+        assert!(filter.check(&post("Rust 异步入门", &[]), link).is_ok());
+        assert!(filter.check(&post("折腾 NAS 记录", &[]), link).is_ok());
+        assert!(filter.check(&post("读书笔记", &[]), link).is_err());
+        assert!(filter.check(&post("Rust 周报", &[]), link).is_err());
+    }
+    
+    #[test]
+    fn excludes_per_friend() {
+        let mut friends = HashMap::new();
+        friends.insert(String::from("https://www.example.com"), FriendFilterConfig {
+            exclude_title: vec![String::from("每周链接分享")],
+            exclude_categories: vec![String::from("Links")],
+        });
+        let filter = PostFilter::new(&FilterConfig { friends, ..config() });
+        
+        let friend = "https://example.com/";
+        assert!(filter.check(&post("每周链接分享 #12", &[]), friend).is_err());
+        assert!(filter.check(&post("春天的照片", &[" links "]), friend).is_err());
+        assert!(filter.check(&post("春天的照片", &["摄影"]), friend).is_ok());
+        // 其他友链不受影响
+        assert!(filter.check(&post("每周链接分享 #12", &["links"]), "https://other.com/").is_ok());
+    }
+    
+    #[test]
+    fn keeps_posts_without_content_regardless_of_length() {
+        let filter = PostFilter::new(&FilterConfig { min_content_length: 200, ..config() });
+        assert!(filter.check(&post("标题", &[]), "https://example.com/").is_ok());
+    }
+}
@@ -0,0 +1,136 @@
+use chrono::{FixedOffset, Utc};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::canonical::clean_url;
+use crate::crawler::decode_html_entities;
+use crate::models::Friends;
+
+const BEIJING_OFFSET: Option<FixedOffset> = FixedOffset::east_opt(8 * 3600);
+
+/// 结构化友链列表的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FriendListFormat {
+    /// 自动识别
+    Auto,
+    /// {"friends": [{name, link, avatar}]} 或 {"friends": [[name, link, avatar]]}
+    Friends,
+    /// butterfly flink / link.yml 分组：[{class_name, link_list: [{name, link, avatar, descr}]}]
+    Flink,
+    /// 平铺列表：[{name, link, avatar}] 或 [[name, link, avatar]]
+    List,
+}
+
+impl FriendListFormat {
+    /// 从配置字符串解析格式，未知格式按自动识别处理
+    pub fn from_name(name: &str) -> FriendListFormat {
+        match name.to_lowercase().as_str() {
+            "friends" => FriendListFormat::Friends,
+            "flink" | "link_yml" | "groups" => FriendListFormat::Flink,
+            "list" | "array" => FriendListFormat::List,
+            _ => FriendListFormat::Auto,
+        }
+    }
+}
+
+/// 解析JSON或YAML文本
+pub fn parse_document(content: &str) -> Result<Value, serde_yaml::Error> {
+    let content = content.trim_start_matches('\u{feff}');
+    match serde_json::from_str(content) {
+        Ok(value) => Ok(value),
+        Err(_) => serde_yaml::from_str(content),
+    }
+}
+
+/// 按格式从文档中提取友链
+pub fn extract_friends(document: &Value, format: FriendListFormat) -> Vec<Friends> {
+    let format = match format {
+        FriendListFormat::Auto => detect_format(document),
+        format => format,
+    };
+    
+    let mut friends = Vec::new();
+    match format {
+        FriendListFormat::Friends => {
+            if let Some(items) = document.get("friends").and_then(|f| f.as_array()) {
+                collect_entries(items, "", &mut friends);
+            }
+        }
+        FriendListFormat::Flink => {
+            let groups = match document {
+                Value::Array(groups) => groups.clone(),
+                Value::Object(_) => vec![document.clone()],
+                _ => Vec::new(),
+            };
+            for group in &groups {
+                let group_name = group.get("class_name").and_then(|c| c.as_str()).unwrap_or("");
+                if let Some(items) = group.get("link_list").and_then(|l| l.as_array()) {
+                    collect_entries(items, group_name, &mut friends);
+                }
+            }
+        }
+        FriendListFormat::List => {
+            if let Some(items) = document.as_array() {
+                collect_entries(items, "", &mut friends);
+            }
+        }
+        FriendListFormat::Auto => {}
+    }
+    
+    friends
+}
+
+/// 根据文档结构识别格式
+fn detect_format(document: &Value) -> FriendListFormat {
+    match document {
+        Value::Object(map) if map.contains_key("friends") => FriendListFormat::Friends,
+        Value::Object(map) if map.contains_key("link_list") => FriendListFormat::Flink,
+        Value::Array(items) if items.iter().any(|item| item.get("link_list").is_some()) => {
+            FriendListFormat::Flink
+        }
+        _ => FriendListFormat::List,
+    }
+}
+
+/// 提取对象或数组形式的友链条目，条目自带 group 字段时优先使用
+fn collect_entries(items: &[Value], group: &str, friends: &mut Vec<Friends>) {
+    let now = Utc::now().with_timezone(&BEIJING_OFFSET.unwrap());
+    let created_at = now.format("%Y-%m-%d %H:%M:%S").to_string();
+    
+    for item in items {
+        let entry = match item {
+            Value::Object(_) => {
+                let name = first_str(item, &["name", "title", "sitename"]).map(|n| decode_html_entities(&n));
+                let link = first_str(item, &["link", "url", "href", "site"]);
+                let avatar = first_str(item, &["avatar", "img", "image", "icon", "logo"]);
+                let group = first_str(item, &["group", "class_name"]).unwrap_or_else(|| group.to_string());
+                name.zip(link).map(|(name, link)| (name, link, avatar.unwrap_or_default(), group))
+            }
+            Value::Array(row) => {
+                let column = |i: usize| row.get(i).and_then(|v| v.as_str()).map(|s| s.to_string());
+                column(0).zip(column(1)).map(|(name, link)| {
+                    (decode_html_entities(&name), link, column(2).unwrap_or_default(), group.to_string())
+                })
+            }
+            _ => None,
+        };
+        
+        if let Some((name, link, avatar, group)) = entry {
+            friends.push(Friends {
+                name,
+                link: clean_url(&link),
+                avatar,
+                group,
+                error: false,
+                created_at: created_at.clone(),
+            });
+        }
+    }
+}
+
+/// 返回第一个存在的字符串字段
+fn first_str(item: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| item.get(*key).and_then(|v| v.as_str()))
+        .map(|s| s.to_string())
+}
@@ -0,0 +1,104 @@
+use whatlang::{Lang, Script};
+
+/// 参与检测的最大字符数，正文较长时只取开头部分
+const DETECT_LENGTH: usize = 2000;
+
+/// 检测文章语言，返回 ISO 639-1 代码（没有两字母代码的语言返回 ISO 639-3 代码），无法判断时返回空字符串
+/// 订阅源声明了语言时优先使用，但声明为拉丁字母等语言而正文明显是中日韩文字时以检测结果为准
+pub fn detect_lang(title: &str, text: &str, declared: &str) -> String {
+    let declared = normalize_lang(declared);
+    let sample: String = format!("{} {}", title, text).chars().take(DETECT_LENGTH).collect();
+    let detected = whatlang::detect(&sample);
+    
+    if !declared.is_empty() {
+        return match detected {
+            Some(info) if is_cjk_script(info.script()) && !is_cjk_lang(&declared) => lang_code(info.lang()),
+            _ => declared,
+        };
+    }
+    
+    match detected {
+        Some(info) if info.is_reliable() => lang_code(info.lang()),
+        // 文本较短时n-gram模型不可靠，但中日韩文字本身足以判断
+        Some(info) if is_cjk_script(info.script()) => lang_code(info.lang()),
+        _ => String::new(),
+    }
+}
+
+/// 规范化订阅源声明的语言：zh-CN、zh_cn 均转为 zh
+pub fn normalize_lang(code: &str) -> String {
+    code.trim()
+        .split(['-', '_'])
+        .next()
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+fn is_cjk_script(script: Script) -> bool {
+    matches!(script, Script::Mandarin | Script::Hiragana | Script::Katakana | Script::Hangul)
+}
+
+fn is_cjk_lang(code: &str) -> bool {
+    matches!(code, "zh" | "ja" | "ko" | "cmn" | "yue" | "jpn" | "kor")
+}
+
+/// whatlang 使用 ISO 639-3 代码，常见语言转换为订阅源中常用的两字母代码
+fn lang_code(lang: Lang) -> String {
+    let code = match lang {
+        Lang::Cmn => "zh",
+        Lang::Eng => "en",
+        Lang::Jpn => "ja",
+        Lang::Kor => "ko",
+        Lang::Rus => "ru",
+        Lang::Ukr => "uk",
+        Lang::Spa => "es",
+        Lang::Por => "pt",
+        Lang::Ita => "it",
+        Lang::Fra => "fr",
+        Lang::Deu => "de",
+        Lang::Nld => "nl",
+        Lang::Pol => "pl",
+        Lang::Tur => "tr",
+        Lang::Vie => "vi",
+        Lang::Tha => "th",
+        Lang::Ind => "id",
+        Lang::Ara => "ar",
+        Lang::Heb => "he",
+        Lang::Hin => "hi",
+        Lang::Swe => "sv",
+        Lang::Dan => "da",
+        Lang::Nob => "nb",
+        Lang::Fin => "fi",
+        Lang::Ces => "cs",
+        Lang::Ell => "el",
+        Lang::Hun => "hu",
+        Lang::Ron => "ro",
+        Lang::Epo => "eo",
+        other => other.code(),
+    };
+    code.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn normalizes_declared_region_codes() {
+        assert_eq!(normalize_lang(" zh-CN "), "zh");
+        assert_eq!(normalize_lang("zh_cn"), "zh");
+        assert_eq!(detect_lang("Hello", "", "zh-CN"), "zh");
+    }
+    
+    #[test]
+    fn cjk_text_overrides_declared_latin_language() {
+        let text = "今天把博客迁移到了新的服务器，顺便整理了一下这些年写过的文章和照片。";
+        assert_eq!(detect_lang("迁移记录", text, "en"), "zh");
+        assert_eq!(detect_lang("Notes", "Some notes about moving the blog.", "en-US"), "en");
+    }
+    
+    #[test]
+    fn short_latin_text_without_declaration_is_unknown() {
+        assert_eq!(detect_lang("Hi", "ok", ""), "");
+    }
+}
@@ -1,26 +1,76 @@
 // 导出需要的类型和函数
 mod models;
+mod canonical;
 mod config;
+mod content;
 mod crawler;
 mod downloader;
+mod error;
+mod feed;
+mod fetch;
+mod filter;
+mod discovery;
+mod friend_list;
+mod lang;
+mod migration;
+mod output;
+mod store;
+mod tagger;
 
-use std::fs::File;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::ExitCode;
 use chrono::{Utc, FixedOffset};
 use tokio::task;
+use crate::canonical::{canonical_key, clean_url, dedupe_friends, dedupe_posts};
 use crate::config::{get_css_rules, get_fc_settings};
-use crate::downloader::{build_client, start_crawl_linkpages, start_crawl_postpages, start_get_friends_links_from_json};
-use crate::models::{AllPostData, Posts};
+use crate::content::process_post_content;
+use crate::discovery::start_discovery;
+use crate::downloader::{
+    build_client, build_friend_client, friend_headers, start_check_reciprocal, start_crawl_linkpages,
+    start_crawl_postpages, start_get_friends_links_from_source,
+};
+use crate::crawler::feed_url_for;
+use crate::error::{Error, Result};
+use crate::filter::PostFilter;
+use crate::migration::suggest_migrations;
+use crate::models::{AllPostData, FriendsData, Posts, ReciprocalStatus, ReciprocityReport, Redirect};
+use crate::output::{write_discovery, write_friends, write_migrations, write_outputs, write_reciprocity, write_tags};
+use crate::store::{CrawlRecord, CrawlStatus, StateStore};
+use crate::tagger::Tagger;
 
 const BEIJING_OFFSET: Option<FixedOffset> = FixedOffset::east_opt(8 * 3600);
+const REQUEST_TIMEOUT: u64 = 10;
+
+/// 没有任何友链抓取成功时的退出码，其余错误的退出码见 Error::exit_code
+const EXIT_ALL_FAILED: u8 = 4;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
     // 初始化日志
     let subscriber = tracing_subscriber::fmt()
         .with_max_level(tracing::Level::INFO)
         .finish();
-    tracing::subscriber::set_global_default(subscriber)?;
+    if let Err(e) = tracing::subscriber::set_global_default(subscriber) {
+        eprintln!("Failed to initialize logging: {}", e);
+        return ExitCode::FAILURE;
+    }
     
+    match run().await {
+        Ok((friends_num, active_num)) if friends_num > 0 && active_num == 0 => {
+            tracing::error!("None of the {} friends could be crawled", friends_num);
+            ExitCode::from(EXIT_ALL_FAILED)
+        }
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            tracing::error!("{} ({})", e, e.category());
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+/// 执行一次完整的抓取，返回 (友链数, 抓取成功的友链数)
+async fn run() -> Result<(usize, usize)> {
     let now = Utc::now().with_timezone(&BEIJING_OFFSET.unwrap());
     tracing::info!("Starting hexo-circle-of-friends-simple at {}", now.format("%Y-%m-%d %H:%M:%S"));
     
@@ -29,18 +79,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // 获取当前工作目录，构建配置文件的绝对路径
     // 这样可以适应不同的环境，如GitHub Action
-    let current_dir = std::env::current_dir()?;
+    let current_dir = std::env::current_dir().map_err(|e| Error::io(Path::new("."), e))?;
     let css_rules_path = current_dir.join("config").join("css_rules.yaml");
     let settings_path = current_dir.join("config").join("settings.yaml");
     
     tracing::info!("CSS rules path: {}", css_rules_path.display());
     tracing::info!("Settings path: {}", settings_path.display());
     
-    let css_rules = get_css_rules(css_rules_path.to_str().ok_or("Failed to convert path to string")?)?;
-    let fc_settings = get_fc_settings(settings_path.to_str().ok_or("Failed to convert path to string")?)?;
+    let css_rules = get_css_rules(&css_rules_path.to_string_lossy())?;
+    let fc_settings = get_fc_settings(&settings_path.to_string_lossy())?;
     
     // 构建HTTP客户端
-    let client = build_client(10, 3);
+    let client = build_client(REQUEST_TIMEOUT, 3, &fc_settings.proxy);
     
     // 爬取友链页面
     let format_base_friends = start_crawl_linkpages(&fc_settings, &css_rules, &client).await;
@@ -52,132 +102,228 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         tracing::info!("Processing configured friends links");
         let mut settings_friends = Vec::new();
         
-        // 处理JSON API或文件中的友链
-        if !fc_settings.settings_friends_links.json_api_or_path.is_empty() {
-            match start_get_friends_links_from_json(
-                &fc_settings.settings_friends_links.json_api_or_path,
-                &client
-            ).await {
-                Ok(json) => {
-                    if let Some(friends) = json.get("friends").and_then(|f| f.as_array()) {
-                        for friend in friends {
-                            if let (Some(name), Some(link)) = (
-                                friend.get("name").and_then(|n| n.as_str()),
-                                friend.get("link").and_then(|l| l.as_str())
-                            ) {
-                                let avatar = friend.get("avatar").and_then(|a| a.as_str()).unwrap_or("");
-                                settings_friends.push(crate::models::Friends {
-                                    name: name.to_string(),
-                                    link: link.to_string(),
-                                    avatar: avatar.to_string(),
-                                    error: false,
-                                    created_at: now.format("%Y-%m-%d %H:%M:%S").to_string(),
-                                });
-                            }
-                        }
-                        tracing::info!("Loaded {} friends from JSON API/file", settings_friends.len());
-                    }
+        // 处理JSON/YAML API或文件中的友链
+        for source in &fc_settings.settings_friends_links.sources {
+            match start_get_friends_links_from_source(source, &fc_settings.http, &client).await {
+                Ok(friends) => {
+                    tracing::info!("Loaded {} friends from {}", friends.len(), source.path);
+                    settings_friends.extend(friends);
                 }
                 Err(e) => {
-                    tracing::error!("Failed to load friends from JSON API/file: {}", e);
+                    tracing::error!("Failed to load friends from {}: {}", source.path, e);
                 }
             }
         }
         
         // 处理手动配置的友链
-        for postpage_vec in &fc_settings.settings_friends_links.list {
-            if postpage_vec.len() >= 3 {
+        for entry in &fc_settings.settings_friends_links.list {
+            if entry.enabled {
                 settings_friends.push(crate::models::Friends {
-                    name: postpage_vec[0].clone(),
-                    link: postpage_vec[1].clone(),
-                    avatar: postpage_vec[2].clone(),
+                    name: entry.name.clone(),
+                    link: entry.link.clone(),
+                    avatar: entry.avatar.clone(),
+                    group: entry.group.clone(),
                     error: false,
                     created_at: now.format("%Y-%m-%d %H:%M:%S").to_string(),
                 });
             }
         }
         
-        // 合并友链并去重
-        all_friends.extend(settings_friends);
+        // 合并友链并按规范化链接去重，配置项友链优先
+        // 配置中停用的友链即使出现在友链页中也不抓取
+        settings_friends.extend(all_friends);
+        all_friends = dedupe_friends(settings_friends);
+        all_friends.retain(|friend| {
+            !fc_settings.settings_friends_links.list
+                .iter()
+                .any(|entry| !entry.enabled && canonical_key(&entry.link) == canonical_key(&friend.link))
+        });
         all_friends.sort_by(|a, b| a.link.cmp(&b.link));
-        all_friends.dedup_by(|a, b| a.link == b.link);
         tracing::info!("Total friends after merging: {}", all_friends.len());
     }
     
+    // 读取持久化状态
+    let mut store = StateStore::load(&fc_settings.state.path);
+    
     // 爬取文章
     tracing::info!("Starting to crawl articles");
     let mut tasks = Vec::new();
+    let mut skipped_friends = Vec::new();
     
     for friend in &all_friends {
+        // 跳过已判定为失效的友链，定期重新检查
+        if fc_settings.health.skip_dead && store.should_skip(&friend.link, &fc_settings.health, now.naive_local()) {
+            tracing::info!("Skipping dead friend {}", friend.name);
+            skipped_friends.push(friend.clone());
+            continue;
+        }
+        
+        // 应用友链的单独设置
+        let entry = if fc_settings.settings_friends_links.enable {
+            let key = canonical_key(&friend.link);
+            fc_settings.settings_friends_links.list.iter().find(|entry| canonical_key(&entry.link) == key)
+        } else {
+            None
+        };
+        // 友链或分组单独设置的文章数上限，友链的设置优先
+        let max_posts = entry
+            .and_then(|entry| entry.max_posts)
+            .or_else(|| fc_settings.group_max_posts(&friend.group));
         let fc_settings_clone = fc_settings.clone();
-        let client_clone = client.clone();
-        let css_rules_clone = css_rules.clone();
+        let client_clone = match entry {
+            Some(entry) if entry.needs_own_client() => build_friend_client(entry, REQUEST_TIMEOUT, &fc_settings.proxy),
+            _ => client.clone(),
+        };
+        let headers = entry.map(friend_headers).unwrap_or_default();
+        let css_rules_clone = match entry {
+            Some(entry) if !entry.theme.is_empty() => css_rules.with_theme(&entry.theme),
+            _ => css_rules.clone(),
+        };
+        let custom_rss = entry.map(|entry| entry.feed_url()).unwrap_or_default();
         let friend_clone = friend.clone();
         
         let task = task::spawn(async move {
             let result = start_crawl_postpages(
                 &friend_clone.link,
                 &fc_settings_clone,
-                String::new(), // 不使用自定义RSS
+                custom_rss.clone(),
+                max_posts,
                 &css_rules_clone,
+                &headers,
                 &client_clone,
             ).await;
             
-            // 将错误转换为字符串以满足Send trait要求
-            let result_str = match result {
-                Ok(posts) => Ok(posts),
-                Err(e) => Err(format!("{:?}", e)),
-            };
-            
-            (friend_clone, result_str)
+            (friend_clone, custom_rss, result)
         });
         
         tasks.push(task);
     }
     
     // 收集爬取结果
+    let post_filter = PostFilter::new(&fc_settings.filters);
+    let tagger = Tagger::new(&fc_settings.tagging);
     let mut success_posts = Vec::new();
+    let mut friend_statuses = Vec::new();
     let mut active_num = 0;
     let mut error_num = 0;
+    // 按错误分类统计出错的友链
+    let mut error_kinds: BTreeMap<String, usize> = BTreeMap::new();
+    let mut filtered_num = 0;
+    let crawled_at = now.format("%Y-%m-%d %H:%M:%S").to_string();
     
     for task in tasks {
         match task.await {
-            Ok((friend, result_str)) => {
-                match result_str {
+            Ok((mut friend, custom_rss, result)) => {
+                let mut crawl = CrawlRecord {
+                    time: crawled_at.clone(),
+                    feed_url: feed_url_for(&friend.link, &custom_rss),
+                    status: CrawlStatus::Ok,
+                    post_count: 0,
+                    message: String::new(),
+                    error_kind: String::new(),
+                    http_status: 0,
+                    link_redirect: Redirect::default(),
+                    feed_redirect: Redirect::default(),
+                };
+                
+                // 没有找到文章同样视为出错，但仍记录重定向
+                let result = match result {
+                    Ok(page) => {
+                        crawl.link_redirect = page.link_redirect;
+                        crawl.feed_redirect = page.feed_redirect;
+                        if page.posts.is_empty() {
+                            Err(Error::NoPosts { url: friend.link.clone() })
+                        } else {
+                            Ok(page.posts)
+                        }
+                    }
+                    Err(e) => Err(e),
+                };
+                
+                match result {
                     Ok(posts) => {
-                        if !posts.is_empty() {
-                            active_num += 1;
-                            // 转换为Posts对象
-                            let posts_with_author: Vec<Posts> = posts
-                                .into_iter()
-                                .map(|post| Posts {
-                                    meta: post,
-                                    author: friend.name.clone(),
-                                    avatar: friend.avatar.clone(),
-                                    created_at: now.format("%Y-%m-%d %H:%M:%S").to_string(),
-                                })
-                                .collect();
-                            let posts_count = posts_with_author.len();
+                        active_num += 1;
+                        crawl.post_count = posts.len();
+                        store.record_articles(&friend, &posts, &crawled_at);
+                        // 按过滤规则剔除文章，再转换为Posts对象
+                        let posts_with_author: Vec<Posts> = posts
+                            .into_iter()
+                            .filter(|post| match post_filter.check(post, &friend.link) {
+                                Ok(()) => true,
+                                Err(reason) => {
+                                    tracing::debug!("Filtered {} from {}: {}", post.link, friend.name, reason);
+                                    filtered_num += 1;
+                                    false
+                                }
+                            })
+                            .map(|mut post| {
+                                post.link = clean_url(&post.link);
+                                process_post_content(&mut post, &fc_settings.content);
+                                if fc_settings.tagging.enable {
+                                    tagger.tag(&mut post);
+                                }
+                                post
+                            })
+                            .map(|post| Posts {
+                                meta: post,
+                                author: friend.name.clone(),
+                                avatar: friend.avatar.clone(),
+                                group: friend.group.clone(),
+                                created_at: now.format("%Y-%m-%d %H:%M:%S").to_string(),
+                            })
+                            .collect();
+                        let posts_count = posts_with_author.len();
+                        if fc_settings.is_group_excluded(&friend.group) {
+                            tracing::info!("Crawled {} posts from {} (group {} excluded from feed)", posts_count, friend.name, friend.group);
+                        } else {
                             success_posts.extend(posts_with_author);
                             tracing::info!("Crawled {} posts from {}", posts_count, friend.name);
-                        } else {
-                            error_num += 1;
-                            tracing::warn!("No posts found for {}", friend.name);
                         }
                     }
                     Err(e) => {
                         error_num += 1;
-                        tracing::error!("Failed to crawl posts from {}: {}", friend.name, e);
+                        *error_kinds.entry(e.category().to_string()).or_insert(0) += 1;
+                        friend.error = true;
+                        if let Error::NoPosts { .. } = e {
+                            crawl.status = CrawlStatus::Empty;
+                            tracing::warn!("No posts found for {}", friend.name);
+                        } else {
+                            crawl.status = CrawlStatus::Error;
+                            tracing::error!("Failed to crawl posts from {} ({}): {}", friend.name, e.category(), e);
+                        }
+                        crawl.error_kind = e.category().to_string();
+                        crawl.http_status = e.status();
+                        crawl.message = e.to_string();
                     }
                 }
+                
+                store.record_crawl(&friend, crawl);
+                friend_statuses.push(store.friend_status(&friend.link, &fc_settings.health, now.naive_local()));
             }
             Err(e) => {
                 error_num += 1;
+                *error_kinds.entry(String::from("internal")).or_insert(0) += 1;
                 tracing::error!("Task failed: {}", e);
             }
         }
     }
     
+    // 跳过的失效友链计入出错数，按上次抓取的错误分类统计
+    for friend in &skipped_friends {
+        error_num += 1;
+        let status = store.friend_status(&friend.link, &fc_settings.health, now.naive_local());
+        let kind = if status.error_kind.is_empty() { "skipped" } else { status.error_kind.as_str() };
+        *error_kinds.entry(kind.to_string()).or_insert(0) += 1;
+        friend_statuses.push(status);
+    }
+    
+    // 按规范化链接去重文章
+    let before_dedupe = success_posts.len();
+    let mut success_posts = dedupe_posts(success_posts);
+    if success_posts.len() < before_dedupe {
+        tracing::info!("Removed {} duplicate posts", before_dedupe - success_posts.len());
+    }
+    
     // 按更新时间排序文章
     success_posts.sort_by(|a, b| {
         b.meta.updated.cmp(&a.meta.updated)
@@ -185,20 +331,92 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // 生成rss.json
     tracing::info!("Generating rss.json");
+    let friends_num = all_friends.len();
     let data = AllPostData::new(
-        all_friends.len(),
+        friends_num,
         active_num,
         error_num,
         success_posts.len(),
         now.format("%Y-%m-%d %H:%M:%S").to_string(),
         success_posts,
         0,
-    );
+    )
+    .with_filtered_num(filtered_num)
+    .with_error_kinds(error_kinds);
+    if filtered_num > 0 {
+        tracing::info!("Filtered out {} posts", filtered_num);
+    }
     
     // 写入文件
-    let file = File::create("rss.json")?;
-    serde_json::to_writer_pretty(file, &data)?;
-    tracing::info!("Data successfully written to rss.json");
+    write_outputs(&data, &fc_settings.output)?;
+    if fc_settings.tagging.index {
+        write_tags(&data, crawled_at.clone(), &fc_settings.output)?;
+    }
+    
+    // 生成friends.json
+    let mut friend_error_kinds: BTreeMap<String, usize> = BTreeMap::new();
+    for status in friend_statuses.iter().filter(|status| status.error) {
+        // 旧版本状态文件中的记录没有错误分类
+        let kind = if status.error_kind.is_empty() { "unknown" } else { status.error_kind.as_str() };
+        *friend_error_kinds.entry(kind.to_string()).or_insert(0) += 1;
+    }
+    let friends_data = FriendsData {
+        friends_num: friend_statuses.len(),
+        error_num: friend_statuses.iter().filter(|status| status.error).count(),
+        error_kinds: friend_error_kinds,
+        last_updated_time: crawled_at.clone(),
+        friends: friend_statuses,
+    };
+    write_friends(&friends_data, &fc_settings.output)?;
+    
+    // 根据永久重定向生成友链地址更新建议
+    if fc_settings.redirects.enable {
+        let report = suggest_migrations(&friends_data.friends, &fc_settings.settings_friends_links.list, crawled_at.clone());
+        for suggestion in &report.suggestions {
+            if !suggestion.new_link.is_empty() {
+                tracing::warn!("{} has moved from {} to {}", suggestion.name, suggestion.link, suggestion.new_link);
+            }
+            if !suggestion.new_feed.is_empty() {
+                tracing::warn!("Feed of {} has moved from {} to {}", suggestion.name, suggestion.feed_url, suggestion.new_feed);
+            }
+        }
+        write_migrations(&report, &fc_settings.output)?;
+    }
+    
+    // 互链检查
+    if fc_settings.reciprocal.enable {
+        tracing::info!("Checking reciprocal links");
+        let mut results = start_check_reciprocal(&all_friends, &fc_settings, &css_rules, &client).await;
+        for result in &mut results {
+            result.removed = store.record_reciprocal(result, &crawled_at);
+        }
+        
+        let report = ReciprocityReport {
+            linked_num: results.iter().filter(|r| r.status == ReciprocalStatus::Linked).count(),
+            missing_num: results.iter().filter(|r| r.status == ReciprocalStatus::Missing).count(),
+            unknown_num: results.iter().filter(|r| r.status == ReciprocalStatus::Unknown).count(),
+            last_updated_time: crawled_at.clone(),
+            removed: results.iter().filter(|r| r.removed).cloned().collect(),
+            friends: results,
+        };
+        for result in &report.removed {
+            tracing::warn!("{} no longer links back from {}", result.name, result.link_page);
+        }
+        write_reciprocity(&report, &fc_settings.output)?;
+    }
+    
+    // 友链发现
+    if fc_settings.discovery.enable {
+        tracing::info!("Discovering friends of friends up to depth {}", fc_settings.discovery.depth);
+        let report = start_discovery(&all_friends, &fc_settings, &css_rules, &client, crawled_at.clone()).await;
+        tracing::info!("Found {} suggested friends", report.suggestions.len());
+        write_discovery(&report, &fc_settings.output)?;
+    }
+    
+    // 保存持久化状态
+    store.trim_history(fc_settings.state.history_size);
+    store.save(&fc_settings.state.path)?;
+    tracing::info!("State saved to {}", fc_settings.state.path);
     
-    Ok(())
+    Ok((friends_num, active_num))
 }
\ No newline at end of file
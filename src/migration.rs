@@ -0,0 +1,52 @@
+use url::Url;
+use crate::canonical::canonical_key;
+use crate::config::FriendEntry;
+use crate::models::{FriendStatus, MigrationReport, MigrationSuggestion};
+
+/// 根据友链主页与订阅源的永久重定向，生成 SETTINGS_FRIENDS_LINKS.list 的地址更新建议
+pub fn suggest_migrations(statuses: &[FriendStatus], list: &[FriendEntry], last_updated_time: String) -> MigrationReport {
+    let mut suggestions = Vec::new();
+    
+    for status in statuses {
+        let link_moved = &status.link_redirect.moved_to;
+        let feed_moved = &status.feed_redirect.moved_to;
+        if link_moved.is_empty() && feed_moved.is_empty() {
+            continue;
+        }
+        
+        let key = canonical_key(&status.link);
+        let entry = list.iter().find(|entry| canonical_key(&entry.link) == key);
+        let link = if link_moved.is_empty() { &status.link } else { link_moved };
+        
+        // 更新主页后，相对路径的 feed 或由主页推断的订阅源可能已经指向新地址，此时无需单独修改 feed
+        let configured_feed = entry.map(|entry| entry.feed.as_str()).unwrap_or("");
+        let expected_feed = if configured_feed.is_empty() {
+            link.clone()
+        } else {
+            Url::parse(link)
+                .and_then(|base| base.join(configured_feed))
+                .map(|url| url.to_string())
+                .unwrap_or_else(|_| configured_feed.to_string())
+        };
+        let new_feed = if !feed_moved.is_empty() && canonical_key(&expected_feed) != canonical_key(feed_moved) {
+            feed_moved.clone()
+        } else {
+            String::new()
+        };
+        
+        suggestions.push(MigrationSuggestion {
+            name: status.name.clone(),
+            link: status.link.clone(),
+            new_link: link_moved.clone(),
+            feed_url: status.feed_url.clone(),
+            new_feed,
+            in_list: entry.is_some(),
+        });
+    }
+    
+    MigrationReport {
+        suggestion_num: suggestions.len(),
+        last_updated_time,
+        suggestions,
+    }
+}
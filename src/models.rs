@@ -1,4 +1,6 @@
+use std::collections::BTreeMap;
 use serde::{Serialize, Deserialize};
+use crate::canonical::stable_id;
 
 /// 文章元数据
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -8,6 +10,23 @@ pub struct PostMeta {
     pub created: String,
     pub updated: String,
     pub content: String, // 文章正文内容
+    pub summary: String, // 纯文本摘要
+    pub guid: String, // 订阅源提供的唯一标识
+    pub tags: Vec<String>, // 分类与标签
+    pub cover: String, // 封面图
+    pub enclosures: Vec<Enclosure>, // 附件，如播客音频
+    pub lang: String, // 语言代码，解析时为订阅源声明的语言
+    pub word_count: usize, // 正文字数
+    pub reading_minutes: usize, // 预计阅读分钟数
+}
+
+/// 文章附件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Enclosure {
+    pub url: String,
+    #[serde(rename = "type")]
+    pub mime_type: String,
+    pub length: u64,
 }
 
 /// 文章数据
@@ -16,6 +35,7 @@ pub struct Posts {
     pub meta: PostMeta,
     pub author: String,
     pub avatar: String,
+    pub group: String,
     pub created_at: String,
 }
 
@@ -25,6 +45,7 @@ pub struct Friends {
     pub name: String,
     pub link: String,
     pub avatar: String,
+    pub group: String,
     pub error: bool,
     pub created_at: String,
 }
@@ -33,35 +54,44 @@ pub struct Friends {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ArticleData {
     pub floor: usize,
+    pub id: String, // 稳定的文章标识
     pub title: String,
     pub created: String,
     pub updated: String,
     pub link: String,
     pub author: String,
     pub avatar: String,
+    pub group: String,
     pub content: String, // 文章正文内容
+    pub summary: String, // 纯文本摘要
+    pub tags: Vec<String>,
+    pub cover: String,
+    pub enclosures: Vec<Enclosure>,
+    pub lang: String,
+    pub word_count: usize,
+    pub reading_minutes: usize,
 }
 
 impl ArticleData {
-    fn new(
-        floor: usize,
-        title: String,
-        created: String,
-        updated: String,
-        link: String,
-        author: String,
-        avatar: String,
-        content: String,
-    ) -> Self {
+    fn new(floor: usize, posts: Posts) -> Self {
         ArticleData {
             floor,
-            title,
-            created,
-            updated,
-            link,
-            author,
-            avatar,
-            content,
+            id: stable_id(&posts.meta.guid, &posts.meta.link),
+            title: posts.meta.title,
+            created: posts.meta.created,
+            updated: posts.meta.updated,
+            link: posts.meta.link,
+            author: posts.author,
+            avatar: posts.avatar,
+            group: posts.group,
+            content: posts.meta.content, // 传递content字段
+            summary: posts.meta.summary,
+            tags: posts.meta.tags,
+            cover: posts.meta.cover,
+            enclosures: posts.meta.enclosures,
+            lang: posts.meta.lang,
+            word_count: posts.meta.word_count,
+            reading_minutes: posts.meta.reading_minutes,
         }
     }
 }
@@ -72,7 +102,10 @@ pub struct StatisticalData {
     friends_num: usize,
     active_num: usize,
     error_num: usize,
+    // 按错误分类统计的出错友链数
+    error_kinds: BTreeMap<String, usize>,
     article_num: usize,
+    filtered_num: usize,
     last_updated_time: String,
 }
 
@@ -88,7 +121,9 @@ impl StatisticalData {
             friends_num,
             active_num,
             error_num,
+            error_kinds: BTreeMap::new(),
             article_num,
+            filtered_num: 0,
             last_updated_time,
         }
     }
@@ -114,18 +149,7 @@ impl AllPostData {
         let article_data: Vec<ArticleData> = posts
             .into_iter()
             .enumerate()
-            .map(|(floor, posts)| {
-                ArticleData::new(
-                    floor + start_offset + 1,
-                    posts.meta.title,
-                    posts.meta.created,
-                    posts.meta.updated,
-                    posts.meta.link,
-                    posts.author,
-                    posts.avatar,
-                    posts.meta.content, // 传递content字段
-                )
-            })
+            .map(|(floor, posts)| ArticleData::new(floor + start_offset + 1, posts))
             .collect();
         AllPostData {
             statistical_data: StatisticalData::new(
@@ -138,4 +162,202 @@ impl AllPostData {
             article_data,
         }
     }
-}
\ No newline at end of file
+    
+    /// 记录被过滤规则剔除的文章数
+    pub fn with_filtered_num(mut self, filtered_num: usize) -> AllPostData {
+        self.statistical_data.filtered_num = filtered_num;
+        self
+    }
+    
+    /// 记录各错误分类的出错友链数
+    pub fn with_error_kinds(mut self, error_kinds: BTreeMap<String, usize>) -> AllPostData {
+        self.statistical_data.error_kinds = error_kinds;
+        self
+    }
+}
+
+/// 分页索引，用于JSON输出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageIndex {
+    pub statistical_data: StatisticalData,
+    pub page_size: usize,
+    pub page_count: usize,
+    pub authors: Vec<AuthorIndex>,
+}
+
+/// 作者索引项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthorIndex {
+    pub author: String,
+    pub slug: String,
+    pub article_num: usize,
+}
+
+/// 单页文章数据，用于JSON输出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageData {
+    pub page: usize,
+    pub page_count: usize,
+    pub article_data: Vec<ArticleData>,
+}
+
+/// 单个作者的文章数据，用于JSON输出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthorPostData {
+    pub author: String,
+    pub avatar: String,
+    pub article_num: usize,
+    pub article_data: Vec<ArticleData>,
+}
+
+/// 标签索引项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagEntry {
+    pub tag: String,
+    pub article_num: usize,
+    pub articles: Vec<String>, // 文章 id
+}
+
+/// 标签索引，用于tags.json输出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagIndex {
+    pub tag_num: usize,
+    pub last_updated_time: String,
+    pub tags: Vec<TagEntry>,
+}
+
+/// 友链健康分类
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FriendHealth {
+    #[default]
+    Healthy,
+    Flaky,
+    Dormant,
+    Dead,
+}
+
+/// 请求经过重定向后的地址
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Redirect {
+    pub final_url: String, // 跟随全部重定向后的地址，没有请求时为空
+    pub moved_to: String,  // 只经过永久重定向（301、308）到达的其他站点地址，没有迁移时为空
+}
+
+/// 友链健康状态，用于friends.json输出
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FriendStatus {
+    pub name: String,
+    pub link: String,
+    pub avatar: String,
+    pub group: String,
+    pub feed_url: String,
+    pub error: bool,
+    pub error_kind: String,
+    pub http_status: u16,
+    pub message: String,
+    pub last_success: String,
+    pub failure_count: usize,
+    pub latest_post: String,
+    pub health: FriendHealth,
+    pub link_redirect: Redirect,
+    pub feed_redirect: Redirect,
+}
+
+/// 所有友链数据，用于friends.json输出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FriendsData {
+    pub friends_num: usize,
+    pub error_num: usize,
+    pub error_kinds: BTreeMap<String, usize>,
+    pub last_updated_time: String,
+    pub friends: Vec<FriendStatus>,
+}
+
+
+/// 互链检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReciprocalStatus {
+    Linked,
+    Missing,
+    Unknown,
+}
+
+/// 单个友链的互链检查结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReciprocalResult {
+    pub name: String,
+    pub link: String,
+    pub link_page: String,
+    pub status: ReciprocalStatus,
+    pub removed: bool,
+}
+
+/// 互链检查报告，用于reciprocity.json输出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReciprocityReport {
+    pub linked_num: usize,
+    pub missing_num: usize,
+    pub unknown_num: usize,
+    pub last_updated_time: String,
+    pub removed: Vec<ReciprocalResult>,
+    pub friends: Vec<ReciprocalResult>,
+}
+
+/// 发现模式中的站点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveredSite {
+    pub name: String,
+    pub link: String,
+    pub avatar: String,
+    pub depth: usize,
+}
+
+/// 站点之间的友链关系，from 的友链页中包含 to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveryEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// 推荐的新友链
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FriendSuggestion {
+    pub name: String,
+    pub link: String,
+    pub avatar: String,
+    pub linked_by_num: usize,
+    pub inbound_num: usize,
+    pub linked_by: Vec<String>,
+}
+
+/// 友链地址更新建议
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationSuggestion {
+    pub name: String,
+    pub link: String,
+    pub new_link: String,
+    pub feed_url: String,
+    pub new_feed: String,
+    pub in_list: bool,
+}
+
+/// 友链迁移报告，用于migrations.json输出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub suggestion_num: usize,
+    pub last_updated_time: String,
+    pub suggestions: Vec<MigrationSuggestion>,
+}
+
+/// 友链发现报告，用于discovery.json输出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveryReport {
+    pub site_num: usize,
+    pub edge_num: usize,
+    pub last_updated_time: String,
+    pub suggestions: Vec<FriendSuggestion>,
+    pub sites: Vec<DiscoveredSite>,
+    pub edges: Vec<DiscoveryEdge>,
+}
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::Path;
+use serde::Serialize;
+use crate::config::OutputConfig;
+use crate::error::{Error, Result};
+use crate::models::{
+    AllPostData, ArticleData, AuthorIndex, AuthorPostData, DiscoveryReport, FriendsData, MigrationReport, PageData, PageIndex,
+    ReciprocityReport, TagEntry, TagIndex,
+};
+
+/// 根据输出配置写出所有结果文件
+pub fn write_outputs(data: &AllPostData, config: &OutputConfig) -> Result<()> {
+    let dir = Path::new(&config.dir);
+    fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+
+    // 完整数据，始终输出
+    write_json(&dir.join("rss.json"), data)?;
+    tracing::info!("Data successfully written to {}", dir.join("rss.json").display());
+
+    // 不含正文的精简数据
+    if config.minified {
+        let path = dir.join("rss.min.json");
+        let mut value = serde_json::to_value(data).map_err(|e| Error::io(&path, e))?;
+        if let Some(articles) = value["article_data"].as_array_mut() {
+            for article in articles {
+                if let Some(obj) = article.as_object_mut() {
+                    obj.remove("content");
+                }
+            }
+        }
+        let file = File::create(&path).map_err(|e| Error::io(&path, e))?;
+        serde_json::to_writer(file, &value).map_err(|e| Error::io(&path, e))?;
+        tracing::info!("Minified data written to {}", path.display());
+    }
+
+    // 按作者拆分
+    let mut authors = Vec::new();
+    if config.per_author {
+        let authors_dir = dir.join("authors");
+        fs::create_dir_all(&authors_dir).map_err(|e| Error::io(&authors_dir, e))?;
+
+        for (slug, author_data) in group_by_author(&data.article_data) {
+            authors.push(AuthorIndex {
+                author: author_data.author.clone(),
+                slug: slug.clone(),
+                article_num: author_data.article_num,
+            });
+            write_json(&authors_dir.join(format!("{}.json", slug)), &author_data)?;
+        }
+        tracing::info!("Wrote {} author files to {}", authors.len(), authors_dir.display());
+    }
+
+    // 分页输出
+    if config.paginate {
+        let pages: Vec<&[ArticleData]> = data.article_data.chunks(config.page_size.max(1)).collect();
+        let page_count = pages.len();
+
+        for (i, chunk) in pages.into_iter().enumerate() {
+            let page = PageData {
+                page: i + 1,
+                page_count,
+                article_data: chunk.to_vec(),
+            };
+            write_json(&dir.join(format!("page-{}.json", i + 1)), &page)?;
+        }
+
+        let index = PageIndex {
+            statistical_data: data.statistical_data.clone(),
+            page_size: config.page_size,
+            page_count,
+            authors,
+        };
+        write_json(&dir.join("index.json"), &index)?;
+        tracing::info!("Wrote index.json and {} page files", page_count);
+    }
+
+    Ok(())
+}
+
+/// 写出友链健康状态 friends.json
+pub fn write_friends(data: &FriendsData, config: &OutputConfig) -> Result<()> {
+    let dir = Path::new(&config.dir);
+    fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+    write_json(&dir.join("friends.json"), data)?;
+    tracing::info!("Friend status written to {}", dir.join("friends.json").display());
+    Ok(())
+}
+
+/// 写出互链检查报告 reciprocity.json
+pub fn write_reciprocity(report: &ReciprocityReport, config: &OutputConfig) -> Result<()> {
+    let dir = Path::new(&config.dir);
+    fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+    write_json(&dir.join("reciprocity.json"), report)?;
+    tracing::info!("Reciprocity report written to {}", dir.join("reciprocity.json").display());
+    Ok(())
+}
+
+/// 写出友链发现报告 discovery.json
+pub fn write_discovery(report: &DiscoveryReport, config: &OutputConfig) -> Result<()> {
+    let dir = Path::new(&config.dir);
+    fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+    write_json(&dir.join("discovery.json"), report)?;
+    tracing::info!("Discovery report written to {}", dir.join("discovery.json").display());
+    Ok(())
+}
+
+/// 写出友链地址更新建议 migrations.json
+pub fn write_migrations(report: &MigrationReport, config: &OutputConfig) -> Result<()> {
+    let dir = Path::new(&config.dir);
+    fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+    write_json(&dir.join("migrations.json"), report)?;
+    tracing::info!("Migration suggestions written to {}", dir.join("migrations.json").display());
+    Ok(())
+}
+
+/// 写出标签索引 tags.json，标签按文章数从多到少排列
+pub fn write_tags(data: &AllPostData, last_updated_time: String, config: &OutputConfig) -> Result<()> {
+    let dir = Path::new(&config.dir);
+    fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+
+    // 不同订阅源的同名标签大小写可能不同，合并后使用首次出现的写法
+    let mut tags: Vec<TagEntry> = Vec::new();
+    let mut index_by_tag: HashMap<String, usize> = HashMap::new();
+    for article in &data.article_data {
+        for tag in &article.tags {
+            let idx = *index_by_tag.entry(tag.to_lowercase()).or_insert_with(|| {
+                tags.push(TagEntry {
+                    tag: tag.clone(),
+                    article_num: 0,
+                    articles: Vec::new(),
+                });
+                tags.len() - 1
+            });
+            if !tags[idx].articles.contains(&article.id) {
+                tags[idx].article_num += 1;
+                tags[idx].articles.push(article.id.clone());
+            }
+        }
+    }
+    tags.sort_by(|a, b| b.article_num.cmp(&a.article_num).then(a.tag.cmp(&b.tag)));
+
+    let index = TagIndex {
+        tag_num: tags.len(),
+        last_updated_time,
+        tags,
+    };
+    write_json(&dir.join("tags.json"), &index)?;
+    tracing::info!("Tag index written to {}", dir.join("tags.json").display());
+    Ok(())
+}
+
+/// 写出格式化的JSON文件
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let file = File::create(path).map_err(|e| Error::io(path, e))?;
+    serde_json::to_writer_pretty(file, value).map_err(|e| Error::io(path, e))?;
+    Ok(())
+}
+
+/// 按作者分组文章，保持原有排序，返回 (slug, 作者数据)
+fn group_by_author(articles: &[ArticleData]) -> Vec<(String, AuthorPostData)> {
+    let mut groups: Vec<(String, AuthorPostData)> = Vec::new();
+    let mut index_by_author: HashMap<&str, usize> = HashMap::new();
+    let mut used_slugs: HashMap<String, usize> = HashMap::new();
+
+    for article in articles {
+        let idx = match index_by_author.get(article.author.as_str()) {
+            Some(idx) => *idx,
+            None => {
+                // 不同作者生成相同slug时追加序号
+                let base = slugify(&article.author);
+                let count = used_slugs.entry(base.clone()).or_insert(0);
+                *count += 1;
+                let slug = if *count == 1 { base } else { format!("{}-{}", base, count) };
+
+                groups.push((slug, AuthorPostData {
+                    author: article.author.clone(),
+                    avatar: article.avatar.clone(),
+                    article_num: 0,
+                    article_data: Vec::new(),
+                }));
+                index_by_author.insert(&article.author, groups.len() - 1);
+                groups.len() - 1
+            }
+        };
+
+        let author_data = &mut groups[idx].1;
+        author_data.article_num += 1;
+        author_data.article_data.push(article.clone());
+    }
+
+    groups
+}
+
+/// 将作者名转换为可用作文件名的slug
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let slug = slug.trim_matches('-').to_string();
+    if slug.is_empty() {
+        String::from("unknown")
+    } else {
+        slug
+    }
+}
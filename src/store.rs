@@ -0,0 +1,381 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::Path;
+use chrono::NaiveDateTime;
+use serde::{Serialize, Deserialize};
+use crate::config::HealthConfig;
+use crate::error::{Error, Result};
+use crate::models::{FriendHealth, FriendStatus, Friends, PostMeta, ReciprocalResult, ReciprocalStatus, Redirect};
+
+/// 单次抓取结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrawlStatus {
+    Ok,
+    Empty,
+    Error,
+}
+
+/// 抓取记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrawlRecord {
+    pub time: String,
+    pub feed_url: String,
+    pub status: CrawlStatus,
+    pub post_count: usize,
+    pub message: String,
+    #[serde(default)]
+    pub error_kind: String, // 错误分类，如 timeout、too_large、content_type
+    #[serde(default)]
+    pub http_status: u16, // 出错时的HTTP状态码，没有时为0
+    #[serde(default)]
+    pub link_redirect: Redirect, // 友链主页经过重定向后的地址
+    #[serde(default)]
+    pub feed_redirect: Redirect, // 订阅源经过重定向后的地址
+}
+
+/// 友链记录
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FriendRecord {
+    pub name: String,
+    pub link: String,
+    pub avatar: String,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub last_success: String,
+    pub failure_count: usize,
+    pub latest_post: String,
+    pub crawls: Vec<CrawlRecord>,
+    #[serde(default)]
+    pub last_linked_back: String,
+    #[serde(default)]
+    pub group: String,
+}
+
+/// 文章记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArticleRecord {
+    pub title: String,
+    pub link: String,
+    pub author_link: String,
+    pub created: String,
+    pub updated: String,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+/// 订阅源元数据
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeedRecord {
+    pub feed_url: String,
+    pub friend_link: String,
+    pub last_fetched: String,
+    pub last_success: String,
+    pub item_count: usize,
+    #[serde(default)]
+    pub final_url: String,
+}
+
+/// 跨运行持久化的状态，保存为本地JSON文件
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StateStore {
+    pub friends: HashMap<String, FriendRecord>,
+    pub articles: HashMap<String, ArticleRecord>,
+    pub feeds: HashMap<String, FeedRecord>,
+}
+
+impl StateStore {
+    /// 读取状态文件，文件不存在或无法解析时返回空状态
+    pub fn load(path: &str) -> StateStore {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => {
+                tracing::info!("No state file at {}, starting fresh", path);
+                return StateStore::default();
+            }
+        };
+        
+        match serde_json::from_str(&content) {
+            Ok(store) => store,
+            Err(e) => {
+                tracing::warn!("Failed to parse state file {}: {}, starting fresh", path, e);
+                StateStore::default()
+            }
+        }
+    }
+    
+    /// 写入状态文件
+    pub fn save(&self, path: &str) -> Result<()> {
+        let path = Path::new(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+        }
+        let file = File::create(path).map_err(|e| Error::io(path, e))?;
+        serde_json::to_writer(file, self).map_err(|e| Error::io(path, e))?;
+        Ok(())
+    }
+    
+    /// 获取或创建友链记录，并刷新基本信息
+    fn friend_entry(&mut self, friend: &Friends, time: &str) -> &mut FriendRecord {
+        let record = self.friends.entry(friend.link.clone()).or_insert_with(|| FriendRecord {
+            link: friend.link.clone(),
+            first_seen: time.to_string(),
+            ..Default::default()
+        });
+        record.name = friend.name.clone();
+        record.avatar = friend.avatar.clone();
+        record.group = friend.group.clone();
+        record.last_seen = time.to_string();
+        record
+    }
+    
+    /// 记录本次抓取到的文章
+    pub fn record_articles(&mut self, friend: &Friends, posts: &[PostMeta], time: &str) {
+        for post in posts {
+            let record = self.articles.entry(post.link.clone()).or_insert_with(|| ArticleRecord {
+                title: String::new(),
+                link: post.link.clone(),
+                author_link: friend.link.clone(),
+                created: String::new(),
+                updated: String::new(),
+                first_seen: time.to_string(),
+                last_seen: String::new(),
+            });
+            record.title = post.title.clone();
+            record.created = post.created.clone();
+            record.updated = post.updated.clone();
+            record.last_seen = time.to_string();
+        }
+        
+        let record = self.friend_entry(friend, time);
+        if let Some(latest) = posts.iter().map(|post| &post.updated).max() {
+            if *latest > record.latest_post {
+                record.latest_post = latest.clone();
+            }
+        }
+    }
+    
+    /// 记录一次抓取尝试，更新友链与订阅源状态
+    pub fn record_crawl(&mut self, friend: &Friends, crawl: CrawlRecord) {
+        if !crawl.feed_url.is_empty() {
+            let feed = self.feeds.entry(crawl.feed_url.clone()).or_insert_with(|| FeedRecord {
+                feed_url: crawl.feed_url.clone(),
+                ..Default::default()
+            });
+            feed.friend_link = friend.link.clone();
+            feed.last_fetched = crawl.time.clone();
+            if crawl.status == CrawlStatus::Ok {
+                feed.last_success = crawl.time.clone();
+                feed.item_count = crawl.post_count;
+                feed.final_url = crawl.feed_redirect.final_url.clone();
+            }
+        }
+        
+        let record = self.friend_entry(friend, &crawl.time);
+        if crawl.status == CrawlStatus::Ok {
+            record.last_success = crawl.time.clone();
+            record.failure_count = 0;
+        } else {
+            record.failure_count += 1;
+        }
+        record.crawls.push(crawl);
+    }
+    
+    /// 记录互链检查结果，返回该友链是否曾经链接本站但本次未找到
+    pub fn record_reciprocal(&mut self, result: &ReciprocalResult, time: &str) -> bool {
+        let record = match self.friends.get_mut(&result.link) {
+            Some(record) => record,
+            None => return false,
+        };
+        
+        match result.status {
+            ReciprocalStatus::Linked => {
+                record.last_linked_back = time.to_string();
+                false
+            }
+            ReciprocalStatus::Missing => !record.last_linked_back.is_empty(),
+            ReciprocalStatus::Unknown => false,
+        }
+    }
+    
+    /// 每个友链只保留最近若干次抓取记录
+    pub fn trim_history(&mut self, history_size: usize) {
+        for record in self.friends.values_mut() {
+            if record.crawls.len() > history_size {
+                let excess = record.crawls.len() - history_size;
+                record.crawls.drain(..excess);
+            }
+        }
+    }
+    
+    /// 根据历史记录判断友链健康状况
+    pub fn health(&self, link: &str, config: &HealthConfig, now: NaiveDateTime) -> FriendHealth {
+        let record = match self.friends.get(link) {
+            Some(record) => record,
+            None => return FriendHealth::Healthy,
+        };
+        
+        // 连续失败达到次数，或自上次成功（从未成功则自首次出现）起失败已超过天数
+        if record.failure_count > 0 {
+            let since = if record.last_success.is_empty() { &record.first_seen } else { &record.last_success };
+            let failing_days = days_since(since, now);
+            if (config.dead_failures > 0 && record.failure_count >= config.dead_failures)
+                || (config.dead_days > 0 && failing_days.is_some_and(|days| days >= config.dead_days))
+            {
+                return FriendHealth::Dead;
+            }
+        }
+        
+        // 可以访问，但长时间没有新文章
+        if record.failure_count == 0 && config.dormant_days > 0 {
+            if let Some(days) = days_since(&record.latest_post, now) {
+                if days >= config.dormant_days {
+                    return FriendHealth::Dormant;
+                }
+            }
+        }
+        
+        if record.crawls.iter().any(|crawl| crawl.status != CrawlStatus::Ok) {
+            return FriendHealth::Flaky;
+        }
+        
+        FriendHealth::Healthy
+    }
+    
+    /// 失效友链在重新检查间隔内跳过抓取
+    pub fn should_skip(&self, link: &str, config: &HealthConfig, now: NaiveDateTime) -> bool {
+        if self.health(link, config, now) != FriendHealth::Dead {
+            return false;
+        }
+        
+        let last_crawl = self.friends.get(link).and_then(|record| record.crawls.last());
+        match last_crawl.and_then(|crawl| days_since(&crawl.time, now)) {
+            Some(days) => days < config.recheck_days,
+            None => false,
+        }
+    }
+    
+    /// 生成friends.json中的友链状态
+    pub fn friend_status(&self, link: &str, config: &HealthConfig, now: NaiveDateTime) -> FriendStatus {
+        let record = match self.friends.get(link) {
+            Some(record) => record,
+            None => return FriendStatus::default(),
+        };
+        let last_crawl = record.crawls.last();
+        
+        FriendStatus {
+            name: record.name.clone(),
+            link: record.link.clone(),
+            avatar: record.avatar.clone(),
+            group: record.group.clone(),
+            feed_url: last_crawl.map(|crawl| crawl.feed_url.clone()).unwrap_or_default(),
+            error: last_crawl.map(|crawl| crawl.status != CrawlStatus::Ok).unwrap_or(false),
+            error_kind: last_crawl.map(|crawl| crawl.error_kind.clone()).unwrap_or_default(),
+            http_status: last_crawl.map(|crawl| crawl.http_status).unwrap_or(0),
+            message: last_crawl.map(|crawl| crawl.message.clone()).unwrap_or_default(),
+            last_success: record.last_success.clone(),
+            failure_count: record.failure_count,
+            latest_post: record.latest_post.clone(),
+            health: self.health(link, config, now),
+            link_redirect: last_crawl.map(|crawl| crawl.link_redirect.clone()).unwrap_or_default(),
+            feed_redirect: last_crawl.map(|crawl| crawl.feed_redirect.clone()).unwrap_or_default(),
+        }
+    }
+}
+
+/// 计算记录时间距今的天数，时间为空或无法解析时返回None
+fn days_since(time: &str, now: NaiveDateTime) -> Option<u64> {
+    let time = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S").ok()?;
+    Some((now - time).num_days().max(0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const LINK: &str = "https://example.com/";
+    
+    fn config() -> HealthConfig {
+        HealthConfig {
+            dormant_days: 90,
+            dead_failures: 5,
+            dead_days: 30,
+            skip_dead: true,
+            recheck_days: 7,
+        }
+    }
+    
+    fn time(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+    
+    fn crawl(store: &mut StateStore, at: &str, status: CrawlStatus) {
+        let friend = Friends {
+            name: String::from("Example"),
+            link: LINK.to_string(),
+            avatar: String::new(),
+            group: String::new(),
+            error: status != CrawlStatus::Ok,
+            created_at: at.to_string(),
+        };
+        store.record_crawl(&friend, CrawlRecord {
+            time: at.to_string(),
+            feed_url: String::new(),
+            status,
+            post_count: 0,
+            message: String::new(),
+            error_kind: String::new(),
+            http_status: 0,
+            link_redirect: Redirect::default(),
+            feed_redirect: Redirect::default(),
+        });
+    }
+    
+    #[test]
+    fn healthy_and_flaky() {
+        let mut store = StateStore::default();
+        crawl(&mut store, "2024-01-01 00:00:00", CrawlStatus::Ok);
+        store.friends.get_mut(LINK).unwrap().latest_post = String::from("2023-12-30 00:00:00");
+        assert_eq!(store.health(LINK, &config(), time("2024-01-02 00:00:00")), FriendHealth::Healthy);
+        
+        // 历史中出现过失败，即使已经恢复
+        crawl(&mut store, "2024-01-02 00:00:00", CrawlStatus::Error);
+        crawl(&mut store, "2024-01-03 00:00:00", CrawlStatus::Ok);
+        assert_eq!(store.health(LINK, &config(), time("2024-01-03 00:00:00")), FriendHealth::Flaky);
+        
+        // 失败次数与天数都未达到阈值
+        crawl(&mut store, "2024-01-04 00:00:00", CrawlStatus::Error);
+        assert_eq!(store.health(LINK, &config(), time("2024-01-04 00:00:00")), FriendHealth::Flaky);
+    }
+    
+    #[test]
+    fn dormant_after_days_without_posts() {
+        let mut store = StateStore::default();
+        crawl(&mut store, "2024-04-01 00:00:00", CrawlStatus::Ok);
+        store.friends.get_mut(LINK).unwrap().latest_post = String::from("2024-01-01 00:00:00");
+        assert_eq!(store.health(LINK, &config(), time("2024-03-30 00:00:00")), FriendHealth::Healthy);
+        assert_eq!(store.health(LINK, &config(), time("2024-03-31 00:00:00")), FriendHealth::Dormant);
+    }
+    
+    #[test]
+    fn dead_by_failures_or_days() {
+        let mut store = StateStore::default();
+        crawl(&mut store, "2024-01-01 00:00:00", CrawlStatus::Ok);
+        for day in 2..=5 {
+            crawl(&mut store, &format!("2024-01-0{} 00:00:00", day), CrawlStatus::Error);
+        }
+        assert_eq!(store.health(LINK, &config(), time("2024-01-05 00:00:00")), FriendHealth::Flaky);
+        // 连续失败达到 dead_failures
+        crawl(&mut store, "2024-01-06 00:00:00", CrawlStatus::Error);
+        assert_eq!(store.health(LINK, &config(), time("2024-01-06 00:00:00")), FriendHealth::Dead);
+        assert!(store.should_skip(LINK, &config(), time("2024-01-12 00:00:00")));
+        assert!(!store.should_skip(LINK, &config(), time("2024-01-13 00:00:00")));
+        
+        // 自上次成功起失败超过 dead_days
+        let mut store = StateStore::default();
+        crawl(&mut store, "2024-01-01 00:00:00", CrawlStatus::Ok);
+        crawl(&mut store, "2024-01-20 00:00:00", CrawlStatus::Error);
+        assert_eq!(store.health(LINK, &config(), time("2024-01-30 00:00:00")), FriendHealth::Flaky);
+        assert_eq!(store.health(LINK, &config(), time("2024-01-31 00:00:00")), FriendHealth::Dead);
+    }
+}
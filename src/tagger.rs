@@ -0,0 +1,129 @@
+use crate::config::TaggingConfig;
+use crate::content::{html_to_text, make_summary};
+use crate::models::PostMeta;
+
+/// 摘要为空时从正文截取的匹配长度
+const EXCERPT_LENGTH: usize = 300;
+
+/// 基于关键词词典的离线标签器
+pub struct Tagger {
+    // (标签, 小写关键词)
+    rules: Vec<(String, Vec<String>)>,
+    max_tags: usize,
+}
+
+impl Tagger {
+    /// 从配置构建标签器，关键词统一转为小写
+    pub fn new(config: &TaggingConfig) -> Tagger {
+        Tagger {
+            rules: config.keywords
+                .iter()
+                .map(|rule| {
+                    let keywords = rule.keywords
+                        .iter()
+                        .map(|k| k.trim().to_lowercase())
+                        .filter(|k| !k.is_empty())
+                        .collect();
+                    (rule.tag.clone(), keywords)
+                })
+                .collect(),
+            max_tags: config.max_tags,
+        }
+    }
+    
+    /// 在标题和摘要中匹配关键词，将命中的标签追加到订阅源提供的分类之后
+    pub fn tag(&self, post: &mut PostMeta) {
+        let excerpt = if post.summary.is_empty() {
+            make_summary(&html_to_text(&post.content), EXCERPT_LENGTH)
+        } else {
+            post.summary.clone()
+        };
+        let text = format!("{}\n{}", post.title, excerpt).to_lowercase();
+        
+        let mut added = 0;
+        for (tag, keywords) in &self.rules {
+            if self.max_tags > 0 && added >= self.max_tags {
+                break;
+            }
+            if post.tags.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+                continue;
+            }
+            if keywords.iter().any(|keyword| contains_keyword(&text, keyword)) {
+                post.tags.push(tag.clone());
+                added += 1;
+            }
+        }
+    }
+}
+
+/// 判断文本是否包含关键词
+/// 中文等没有空格分词的关键词直接按子串匹配，英文关键词要求前后不是字母或数字，避免 go 匹配到 google
+fn contains_keyword(text: &str, keyword: &str) -> bool {
+    if !keyword.is_ascii() {
+        return text.contains(keyword);
+    }
+    
+    let is_word = |c: char| c.is_ascii_alphanumeric();
+    text.match_indices(keyword).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + keyword.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TagKeywords;
+    
+    fn tagger(rules: &[(&str, &[&str])]) -> Tagger {
+        Tagger::new(&TaggingConfig {
+            enable: true,
+            index: false,
+            max_tags: 0,
+            keywords: rules
+                .iter()
+                .map(|(tag, keywords)| TagKeywords {
+                    tag: tag.to_string(),
+                    keywords: keywords.iter().map(|k| k.to_string()).collect(),
+                })
+                .collect(),
+        })
+    }
+    
+    #[test]
+    fn latin_keywords_match_whole_words() {
+        assert!(contains_keyword("learning go in 2024", "go"));
+        assert!(contains_keyword("go, rust and c++", "go"));
+        assert!(!contains_keyword("google search tips", "go"));
+        assert!(!contains_keyword("ergo", "go"));
+    }
+    
+    #[test]
+    fn cjk_keywords_match_substrings() {
+        assert!(contains_keyword("周末去拍摄影展", "摄影"));
+        assert!(contains_keyword("用rust写爬虫", "爬虫"));
+    }
+    
+    #[test]
+    fn merges_tags_ignoring_case() {
+        let tagger = tagger(&[("Rust", &["rust"]), ("摄影", &["摄影"])]);
+        let mut post = PostMeta {
+            title: String::from("Rust 与摄影"),
+            link: String::from("https://example.com/post/1"),
+            created: String::new(),
+            updated: String::new(),
+            content: String::new(),
+            summary: String::from("摘要"),
+            guid: String::new(),
+            tags: vec![String::from("rust")],
+            cover: String::new(),
+            enclosures: Vec::new(),
+            lang: String::new(),
+            word_count: 0,
+            reading_minutes: 0,
+        };
+        tagger.tag(&mut post);
+        assert_eq!(post.tags, vec!["rust", "摄影"]);
+    }
+}